@@ -16,80 +16,564 @@
 
 use crate::{
     egui_data::EGUIData,
-    espanso_yaml::{EspansoYaml, YamlPairs},
+    espanso_yaml::{form_field_count, quoted_in_yaml, read_yaml_text, EspansoYaml, YamlPairs},
     parse_config::ParsedConfig,
+    snippet_packs::SNIPPET_PACKS,
     style,
 };
 
-use dirs::config_dir;
+use dirs::{config_dir, data_local_dir};
 use home;
+use iced::futures::{SinkExt, StreamExt};
 use iced::{
     alignment,
     keyboard::{self, key},
     widget::{
-        self, button, center, column, container, horizontal_space, mouse_area, opaque, pick_list,
-        row, scrollable, stack, text, text_editor, text_input, toggler, tooltip, Button, Column,
-        Container, Scrollable, Space, Theme, Tooltip,
+        self, button, center, checkbox, column, container, horizontal_rule, horizontal_space,
+        image, mouse_area, opaque, pick_list, row, scrollable, stack, text, text_editor,
+        text_input, toggler, tooltip, Button, Column, Container, Scrollable, Space, Theme, Tooltip,
     },
-    Alignment, Color, Element, Length, Padding, Renderer, Subscription, Task,
+    Alignment, Color, Element, Font, Length, Padding, Renderer, Subscription, Task,
 };
 use iced_aw::{number_input, Card};
 use iced_fonts::{nerd::icon_to_char, Nerd, NERD_FONT};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use rfd::FileDialog;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::fs::{create_dir, remove_file, rename, File, OpenOptions};
+use std::fs::{copy, create_dir_all, metadata, read_dir, remove_file, rename, File, OpenOptions};
 use std::io::{Read, Write};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use walkdir::WalkDir;
 
 static SCROLLABLE_ID: Lazy<scrollable::Id> = Lazy::new(scrollable::Id::unique);
+// Shown in the new-file folder list for match/ itself
+const NEW_FILE_ROOT_FOLDER: &str = "match/";
+const SORT_OPTIONS: [&str; 2] = ["Trigger A-Z", "Trigger Z-A"];
+const MAX_RECENT_DIRS: usize = 5;
+const MAX_RECENT_TOASTS: usize = 10;
+const MAX_CONFIG_HISTORY: usize = 100;
+const ACCENT_PRESETS: [&str; 5] = ["#3D7EFF", "#2E9E6A", "#D9480F", "#C2255C", "#7048E8"];
+// Bounds of the numeric config options, wide enough for any sensible value.
+// Delays are in milliseconds.
+const DELAY_RANGE: RangeInclusive<usize> = 0..=60_000;
+const CLIPBOARD_THRESHOLD_RANGE: RangeInclusive<usize> = 0..=1_000_000;
+const BACKSPACE_LIMIT_RANGE: RangeInclusive<usize> = 0..=1000;
+// A negative interval means nothing useful to espanso, so it isn't offered
+const LAYOUT_CACHE_INTERVAL_RANGE: RangeInclusive<i64> = 0..=3_600_000;
+const BINARY_FILE_REASON: &str = "looks like a binary file rather than YAML text.";
+// espanso skips match files whose names start with an underscore, so disabled
+// files are moved into this folder with an underscore prepended
+const DISABLED_DIR: &str = "_disabled";
+const PRESENT_MODE_OPTIONS: [&str; 7] = [
+    "default",
+    "vsync",
+    "no_vsync",
+    "immediate",
+    "fifo",
+    "fifo_relaxed",
+    "mailbox",
+];
+const BACKEND_OPTIONS: [&str; 3] = ["Auto", "Clipboard", "Inject"];
+const TOGGLE_KEY_OPTIONS: [&str; 13] = [
+    "OFF",
+    "CTRL",
+    "ALT",
+    "SHIFT",
+    "META",
+    "LEFT_CTRL",
+    "LEFT_ALT",
+    "LEFT_SHIFT",
+    "LEFT_META",
+    "RIGHT_CTRL",
+    "RIGHT_ALT",
+    "RIGHT_SHIFT",
+    "RIGHT_META",
+];
 
 pub struct EGUI {
     espanso_loc: String,
+    recent_dirs: Vec<String>,
+    software_renderer: bool,
+    present_mode: Option<String>,
+    antialiasing: bool,
+    high_contrast: bool,
+    browser_command: String,
+    accent_color: String,
+    large_file_threshold: usize,
+    // Matches in each enabled file, counted when match/ is scanned
+    match_counts: BTreeMap<String, usize>,
+    max_file_size_mb: u64,
+    // Match files the scan found too large or binary to open, with the reason
+    unopenable_files: BTreeMap<String, String>,
+    preserve_empty_matches: bool,
+    // Matches listed one line each; a clicked row opens as a full card
+    compact_view: bool,
+    file_compact_views: BTreeMap<String, bool>,
+    always_multiline_replace: bool,
+    warn_cursor_hints: bool,
+    // Write multi-line replacements as | blocks rather than escaped strings
+    block_scalar_replace: bool,
+    // Cards switched to the multi-line replace editor since the file was loaded
+    multiline_replace: BTreeSet<usize>,
+    compact_expanded: Option<usize>,
+    // Match that came back different when the file was test-serialized on save
+    round_trip_error: Option<usize>,
     selected_nav: String,
     directory_invalid: bool,
     selected_file: PathBuf,
+    selected_file_modified: Option<SystemTime>,
     original_file: EspansoYaml,
     edited_file: EspansoYaml,
     edited_file_te: Vec<text_editor::Content>,
+    quick_add_te: text_editor::Content,
+    selected_matches: BTreeSet<usize>,
+    // Card being dragged by its handle and the position it would drop into
+    dragging: Option<usize>,
+    drag_target: Option<usize>,
+    // Search terms being edited, as typed, for the cards that have them open
+    search_terms_input: BTreeMap<usize, String>,
+    // How many matches in the open file use each trigger
+    trigger_counts: BTreeMap<String, usize>,
+    expanded_replace: Option<usize>,
+    yaml_preview: Option<text_editor::Content>,
+    // Match files with a problem, and what it is, from the last check
+    validation_report: Option<Vec<(String, String)>>,
+    // Another config's file name and the options where it differs from the edited one
+    config_comparison: Option<(String, Vec<ConfigDifference>)>,
+    // Literal text and variable name while previewing an extraction of the
+    // selected matches' shared text into a global var
+    extract_var: Option<(String, String)>,
+    // Why saving the open file would change content the editor doesn't show
+    lossy_file: Option<&'static str>,
+    trigger_search: String,
+    // Files with a trigger containing the search, and those triggers. The nav
+    // lists only these while it's set.
+    trigger_search_results: Option<BTreeMap<String, Vec<String>>>,
+    expanded_search_file: Option<String>,
+    // Pasted triggers waiting for the replace text they'll share
+    paste_list: Option<(Vec<String>, String)>,
+    test_output: Option<text_editor::Content>,
+    governing_configs: Vec<String>,
+    show_global_vars: bool,
+    global_vars_te: text_editor::Content,
+    // A preserved match being edited as raw YAML, by its index in preserved_matches
+    raw_match_edit: Option<(usize, text_editor::Content)>,
+    global_vars_error: Option<String>,
     original_config: ParsedConfig,
     edited_config: ParsedConfig,
+    // Config fields the user changed since the config was loaded or saved
+    touched_config: BTreeSet<&'static str>,
+    // Config before each edit, newest last, with the field that was edited
+    config_history: Vec<(&'static str, ParsedConfig)>,
+    // Field of the last recorded edit, so typing into it is one step
+    config_history_field: Option<&'static str>,
     temp_word_separators: String,
+    config_presets: BTreeMap<String, ParsedConfig>,
+    preset_name: String,
     match_files: Vec<String>,
+    disabled_match_files: Vec<String>,
+    logs: text_editor::Content,
+    // Where each match file was scrolled to, restored when it's opened again
+    scroll_offsets: BTreeMap<PathBuf, scrollable::RelativeOffset>,
+    logs_loading: bool,
+    // Files found so far while a scan of match/ is running
+    scan_progress: Option<usize>,
+    rescan_pending: bool,
+    // Files checked so far while "Check all match files" is running
+    validation_progress: Option<usize>,
+    // File to open when the first scan finishes, from the command line
+    pending_open: Option<String>,
     show_modal: bool,
+    show_shortcuts: bool,
+    nav_highlight: Option<String>,
     modal_title: String,
     modal_description: String,
     modal_ok_text: String,
-    nav_queue: String,
+    modal_action: Option<PendingAction>,
+    modal_alt_action: Option<(String, PendingAction)>,
     show_new_file_input: bool,
     new_file_name: String,
+    // Folder under match/ new files go in, empty for match/ itself
+    new_file_folder: String,
+    match_subfolders: Vec<String>,
     file_name_change: String,
+    onboarding: bool,
+    toast: Option<String>,
+    auto_save: bool,
+    auto_save_delay: u64,
+    last_activity: Option<Instant>,
+    // Result of the last automatic save, cleared by the next interaction
+    auto_save_status: Option<String>,
+    recent_toasts: Vec<String>,
+    // The last deleted file's stem, whether it was disabled and its contents,
+    // kept so the deletion toast can undo it
+    deleted_file: Option<(String, bool, Vec<u8>)>,
+}
+
+// What the modal's buttons do once the user confirms
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    Navigate(String),
+    DeleteFile,
+    DuplicateFile,
+    FormatFile,
+    DeleteSelectedMatches,
+    OverwriteFile,
+    FixCursorHintsAndSave,
+    SaveWithCursorHints,
+    OverwriteConfig,
+    SaveConfigAnyway,
+    Reload,
+    ReloadAll,
+    UseEspansoDir(String),
+    RecreateMatchDir,
+    CreateDefaultConfig(String),
+    MoveSelectedAnyway(String),
+}
+
+impl Message {
+    // The config field a Config screen message edits
+    fn config_field(&self) -> Option<&'static str> {
+        match self {
+            Message::BackendPicked(_) => Some("backend"),
+            Message::EnableToggled(_) => Some("enable"),
+            Message::ToggleKeyPicked(_) => Some("toggle_key"),
+            Message::InjectDelayInput(_) => Some("inject_delay"),
+            Message::KeyDelayInput(_) => Some("key_delay"),
+            Message::ClipboardThresholdInput(_) => Some("clipboard_threshold"),
+            Message::PasteShortcutInput(_) => Some("paste_shortcut"),
+            Message::SearchShortcutInput(_) => Some("search_shortcut"),
+            Message::SearchTriggerInput(_) => Some("search_trigger"),
+            Message::LabelInput(_) => Some("label"),
+            Message::PrePasteDelayInput(_) => Some("pre_paste_delay"),
+            Message::X11FastInjectToggled(_) => Some("disable_x11_fast_inject"),
+            Message::PasteShortcutEventDelayInput(_) => Some("paste_shortcut_event_delay"),
+            Message::AutoRestartToggled(_) => Some("auto_restart"),
+            Message::PreserveClipboardToggled(_) => Some("preserve_clipboard"),
+            Message::RestoreClipboardDelayInput(_) => Some("restore_clipboard_delay"),
+            Message::EvdevModifierDelayInput(_) => Some("evdev_modifier_delay"),
+            Message::WordSeparatorsInput(_) => Some("word_separators"),
+            Message::BackspaceLimitInput(_) => Some("backspace_limit"),
+            Message::ApplyPatchToggled(_) => Some("apply_patch"),
+            Message::KeyboardLayoutInput(_) => Some("keyboard_layout"),
+            Message::UndoBackspaceToggled(_) => Some("undo_backspace"),
+            Message::ShowNotificationsToggled(_) => Some("show_notifications"),
+            Message::ShowIconToggled(_) => Some("show_icon"),
+            Message::UseXclipBackendToggled(_) => Some("x11_use_xclip_backend"),
+            Message::ExcludeOrphanEventsToggled(_) => Some("win32_exclude_orphan_events"),
+            Message::KeyboardLayoutCacheIntervalInput(_) => {
+                Some("win32_keyboard_layout_cache_interval")
+            }
+            _ => None,
+        }
+    }
+}
+
+// An option set differently in the edited config and one it's compared with,
+// with each value as shown to the user
+struct ConfigDifference {
+    option: String,
+    edited: String,
+    other: String,
+}
+
+// A keyboard shortcut, both handled and listed in the shortcut help from
+// SHORTCUTS so the two can't drift apart
+struct Shortcut {
+    key: ShortcutKey,
+    // Cmd on macOS, Ctrl elsewhere
+    command: bool,
+    // Ctrl on every platform, for shortcuts macOS already uses with Cmd
+    control: bool,
+    shift: bool,
+    // Handled while a dialog is open instead of in the main window
+    in_dialog: bool,
+    action: &'static str,
+    message: Message,
+}
+
+enum ShortcutKey {
+    Named(key::Named),
+    Character(&'static str),
+}
+
+const fn shortcut(key: ShortcutKey, action: &'static str, message: Message) -> Shortcut {
+    Shortcut {
+        key,
+        command: false,
+        control: false,
+        shift: false,
+        in_dialog: false,
+        action,
+        message,
+    }
+}
+
+const SHORTCUTS: [Shortcut; 15] = [
+    shortcut(
+        ShortcutKey::Character("s"),
+        "Save the open file or config",
+        Message::SaveShortcutPressed,
+    )
+    .with_command(),
+    shortcut(
+        ShortcutKey::Named(key::Named::Tab),
+        "Next file",
+        Message::CycleFilePressed(1),
+    )
+    .with_control(),
+    shortcut(
+        ShortcutKey::Named(key::Named::Tab),
+        "Previous file",
+        Message::CycleFilePressed(-1),
+    )
+    .with_control()
+    .with_shift(),
+    shortcut(
+        ShortcutKey::Named(key::Named::Tab),
+        "Next field",
+        Message::TabPressed { shift: false },
+    ),
+    shortcut(
+        ShortcutKey::Named(key::Named::Tab),
+        "Previous field",
+        Message::TabPressed { shift: true },
+    )
+    .with_shift(),
+    shortcut(
+        ShortcutKey::Named(key::Named::ArrowUp),
+        "Highlight the file above",
+        Message::NavMoved(-1),
+    ),
+    shortcut(
+        ShortcutKey::Named(key::Named::ArrowDown),
+        "Highlight the file below",
+        Message::NavMoved(1),
+    ),
+    shortcut(
+        ShortcutKey::Named(key::Named::Enter),
+        "Open the highlighted file",
+        Message::NavOpenPressed,
+    ),
+    shortcut(
+        ShortcutKey::Named(key::Named::Escape),
+        "Clear the highlight",
+        Message::NavEscapePressed,
+    ),
+    shortcut(
+        ShortcutKey::Character("z"),
+        "Undo the last config change",
+        Message::UndoConfigStepPressed,
+    )
+    .with_command(),
+    shortcut(
+        ShortcutKey::Character("?"),
+        "Show keyboard shortcuts",
+        Message::ShortcutHelpToggled,
+    ),
+    shortcut(
+        ShortcutKey::Named(key::Named::Enter),
+        "Confirm",
+        Message::ModalOkPressed,
+    )
+    .with_dialog(),
+    shortcut(
+        ShortcutKey::Named(key::Named::Escape),
+        "Cancel",
+        Message::ModalCancelPressed,
+    )
+    .with_dialog(),
+    shortcut(
+        ShortcutKey::Named(key::Named::Tab),
+        "Next button",
+        Message::TabPressed { shift: false },
+    )
+    .with_dialog(),
+    shortcut(
+        ShortcutKey::Named(key::Named::Tab),
+        "Previous button",
+        Message::TabPressed { shift: true },
+    )
+    .with_dialog()
+    .with_shift(),
+];
+
+impl Shortcut {
+    const fn with_command(mut self) -> Self {
+        self.command = true;
+        self
+    }
+
+    const fn with_control(mut self) -> Self {
+        self.control = true;
+        self
+    }
+
+    const fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    const fn with_dialog(mut self) -> Self {
+        self.in_dialog = true;
+        self
+    }
+
+    fn matches(&self, key: &keyboard::Key, modifiers: keyboard::Modifiers) -> bool {
+        let key_matches = match (&self.key, key) {
+            (ShortcutKey::Named(named), keyboard::Key::Named(pressed)) => {
+                named == pressed && self.shift == modifiers.shift()
+            }
+            // Shift is part of typing some characters, such as ?
+            (ShortcutKey::Character(character), keyboard::Key::Character(pressed)) => {
+                pressed.as_str() == *character
+            }
+            _ => false,
+        };
+        let macos = cfg!(target_os = "macos");
+        key_matches
+            && modifiers.control() == (self.control || (self.command && !macos))
+            && modifiers.logo() == (self.command && macos)
+    }
+
+    fn keys(&self) -> String {
+        let mut keys = Vec::new();
+        if self.command {
+            keys.push(
+                if cfg!(target_os = "macos") {
+                    "Cmd"
+                } else {
+                    "Ctrl"
+                }
+                .to_string(),
+            );
+        }
+        if self.control {
+            keys.push("Ctrl".to_string());
+        }
+        if self.shift {
+            keys.push("Shift".to_string());
+        }
+        keys.push(match &self.key {
+            ShortcutKey::Named(key::Named::ArrowUp) => "Up".to_string(),
+            ShortcutKey::Named(key::Named::ArrowDown) => "Down".to_string(),
+            ShortcutKey::Named(key::Named::Escape) => "Esc".to_string(),
+            ShortcutKey::Named(named) => format!("{:?}", named),
+            ShortcutKey::Character(character) => character.to_uppercase(),
+        });
+        keys.join("+")
+    }
+}
+
+fn shortcut_message(
+    key: keyboard::Key,
+    modifiers: keyboard::Modifiers,
+    in_dialog: bool,
+) -> Option<Message> {
+    SHORTCUTS
+        .iter()
+        .find(|shortcut| shortcut.in_dialog == in_dialog && shortcut.matches(&key, modifiers))
+        .map(|shortcut| shortcut.message.clone())
+}
+
+impl PendingAction {
+    fn is_destructive(&self) -> bool {
+        matches!(
+            self,
+            PendingAction::DeleteFile | PendingAction::DeleteSelectedMatches
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     AddPairPressed,
     EspansoDirInputChanged(String),
+    RecentDirPicked(String),
+    SoftwareRendererToggled(bool),
+    PresentModePicked(String),
+    AntialiasingToggled(bool),
+    HighContrastToggled(bool),
+    BrowserCommandInput(String),
+    AccentColorInput(String),
+    LargeFileThresholdInput(usize),
+    PreserveEmptyMatchesToggled(bool),
+    AlwaysMultilineReplaceToggled(bool),
+    WarnCursorHintsToggled(bool),
+    BlockScalarReplaceToggled(bool),
+    FixCursorHintsPressed(usize),
+    ReplaceNewlinePressed(usize),
+    CompactViewToggled,
+    CompactRowPressed(usize),
     YamlInputChanged(String, usize, String),
     BrowsePressed,
+    DetectEspansoDirPressed,
+    ExportAllPressed,
+    CreateEspansoDirPressed,
+    RetryEspansoDirPressed,
     SettingsSavePressed,
     NavigateTo(String),
     ResetPressed,
     SaveFilePressed,
+    SaveShortcutPressed,
+    ShortcutHelpToggled,
     ModalCancelPressed,
     ModalOkPressed,
+    ModalAltPressed,
     CloseModal,
-    ShowModal(String, String, String),
+    ConfirmLeaveFile(PendingAction),
     EditReplace(text_editor::Action, usize),
+    ExpandReplacePressed(usize),
+    GlobalVarsToggled,
+    EditGlobalVars(text_editor::Action),
+    EditRawMatchPressed(usize),
+    RawMatchAction(text_editor::Action),
+    ApplyRawMatchPressed,
+    CancelRawMatchPressed,
+    CloseExpandedReplace,
+    TestMatchPressed(usize),
+    RevertMatchPressed(usize),
+    WordToggled(usize, bool),
+    SwapTriggerReplacePressed(usize),
+    TrimTriggerPressed(usize),
+    TrimAllTriggersPressed,
+    NoteInput(usize, String),
+    SearchTermsToggled(usize),
+    SearchTermsInput(usize, String),
+    PickImagePressed(usize),
+    TestOutputAction(text_editor::Action),
+    CloseTestOutput,
+    PreviewYamlPressed,
+    YamlPreviewAction(text_editor::Action),
+    YamlPreviewSavePressed,
+    CloseYamlPreview,
+    CompareConfigPressed,
+    ValidateMatchFilesPressed,
+    CloseValidationReport,
+    CloseConfigComparison,
+    QuickAddTriggerInput(String),
+    QuickAddReplace(text_editor::Action),
     AddFilePressed,
     NewFileInputChanged(String),
+    NewFileFolderPicked(String),
     SubmitNewFileName,
     FileNameChangeInputChanged(String),
     FileNameChangeSubmit,
     DeleteFilePressed,
+    DuplicateFilePressed,
+    OpenExternalFilePressed,
+    ExportPackagePressed,
+    FormatFilePressed,
+    SortPicked(String),
+    DragStarted(usize),
+    DragHovered(usize),
+    DragReleased,
+    MoveRow { from: usize, to: usize },
+    FileEnabledToggled(bool),
     BackendPicked(String),
     EnableToggled(bool),
     ToggleKeyPicked(String),
@@ -99,6 +583,7 @@ pub enum Message {
     PasteShortcutInput(String),
     SearchShortcutInput(String),
     SearchTriggerInput(String),
+    LabelInput(String),
     PrePasteDelayInput(usize),
     X11FastInjectToggled(bool),
     PasteShortcutEventDelayInput(usize),
@@ -118,10 +603,66 @@ pub enum Message {
     KeyboardLayoutCacheIntervalInput(i64),
     SaveConfigPressed,
     UndoConfigPressed,
+    UndoConfigStepPressed,
     ResetConfigPressed,
+    PresetPicked(String),
+    PresetNameInput(String),
+    SavePresetPressed,
+    DeletePresetPressed,
     LaunchURL(String),
     DeleteRowPressed(usize),
+    InsertRow(usize),
+    CopyMatchYamlPressed(usize),
+    CopyTriggerPressed(usize),
+    CopyReplacePressed(usize),
+    ExternalEditPressed(usize),
+    // Index, the replace text sent to the editor, and what came back
+    ExternalEditFinished(usize, String, Result<String, String>),
+    MatchSelected(usize, bool),
+    ClearSelectionPressed,
+    CopySelectedYamlPressed,
+    ExtractVarPressed,
+    ExtractVarTextInput(String),
+    ExtractVarNameInput(String),
+    ApplyExtractVarPressed,
+    CloseExtractVar,
+    DeleteSelectedPressed,
+    MoveSelectedTo(String),
+    // Index into SNIPPET_PACKS and the file to add it to
+    ImportPackTo(usize, String),
+    PasteYamlPressed,
+    PasteYamlReceived(Option<String>),
+    PasteListPressed,
+    PasteListReceived(Option<String>),
+    PasteListReplaceInput(String),
+    ApplyPasteListPressed,
+    ClosePasteList,
+    TriggerSearchInput(String),
+    TriggerSearchSubmit,
+    SearchResultFileToggled(String),
+    DismissToast,
+    UndoDeletePressed,
+    NavMoved(isize),
+    NavOpenPressed,
+    NavEscapePressed,
+    ReloadAllPressed,
+    CycleFilePressed(isize),
     TabPressed { shift: bool },
+    FileScrolled(scrollable::RelativeOffset),
+    RefreshLogsPressed,
+    LogsLoaded(String),
+    LogsAction(text_editor::Action),
+    CopyLogsPressed,
+    AutoSaveToggled(bool),
+    AutoSaveDelayInput(u64),
+    AutoSaveTick,
+    CopyDebugInfoPressed,
+    ScanProgress(usize),
+    ScanFinished(PathBuf, Vec<String>, Vec<String>),
+    MatchCountsFound(PathBuf, BTreeMap<String, usize>, BTreeMap<String, String>),
+    MaxFileSizeInput(u64),
+    ValidationProgress(usize),
+    ValidationFinished(PathBuf, Vec<(String, String)>),
 }
 
 impl Default for EGUI {
@@ -131,64 +672,140 @@ impl Default for EGUI {
 }
 
 impl EGUI {
-    pub fn new() -> Self {
-        let egui_data = match read_egui_data() {
-            Ok(data) => data,
-            Err(_) => EGUIData {
-                espanso_dir: get_default_espanso_dir(),
-            },
+    pub fn boot(open_file: Option<PathBuf>) -> (Self, Task<Message>) {
+        let mut egui = Self::new();
+        if let Some(path) = open_file {
+            match locate_espanso_file(&path) {
+                Some((espanso_dir, destination)) => {
+                    // Opened once the scan knows whether the file is disabled
+                    egui.espanso_loc = espanso_dir;
+                    egui.pending_open = Some(destination);
+                    let scan = egui.activate_espanso_dir();
+                    return (egui, scan);
+                }
+                None => {
+                    egui.toast = Some(format!(
+                        "{} isn't a match file or config in an espanso folder",
+                        path.display()
+                    ))
+                }
+            }
+        }
+        let scan = if egui.directory_invalid {
+            Task::none()
+        } else {
+            egui.refresh_match_files()
         };
-        if valid_espanso_dir(egui_data.espanso_dir.clone()) {
+        (egui, scan)
+    }
+
+    pub fn new() -> Self {
+        let (egui_data, load_warning) = load_egui_data();
+        let mut recent_dirs = egui_data.recent_dirs.clone();
+        recent_dirs.retain(|dir| valid_espanso_dir(dir.clone()));
+        let valid_dir = valid_espanso_dir(egui_data.espanso_dir.clone());
+        if valid_dir {
+            add_recent_dir(&mut recent_dirs, &egui_data.espanso_dir);
             let new_egui_data = EGUIData {
-                espanso_dir: egui_data.espanso_dir.clone(),
+                recent_dirs: recent_dirs.clone(),
+                ..egui_data.clone()
             };
             let _ = write_egui_data(&new_egui_data);
-            EGUI {
-                espanso_loc: egui_data.espanso_dir.clone(),
-                selected_nav: "eg-Settings".to_string(),
-                directory_invalid: false,
-                selected_file: PathBuf::new(),
-                original_file: EspansoYaml::default(),
-                edited_file: EspansoYaml::default(),
-                edited_file_te: Vec::new(),
-                match_files: {
-                    let default_path = PathBuf::from(egui_data.espanso_dir.clone());
-                    get_all_match_file_stems(default_path.join("match"))
-                },
-                original_config: ParsedConfig::default(),
-                edited_config: ParsedConfig::default(),
-                temp_word_separators: String::new(),
-                show_modal: false,
-                modal_title: String::new(),
-                modal_description: String::new(),
-                modal_ok_text: "OK".to_string(),
-                nav_queue: String::new(),
-                show_new_file_input: false,
-                new_file_name: String::new(),
-                file_name_change: String::new(),
-            }
-        } else {
-            EGUI {
-                espanso_loc: String::new(),
-                selected_nav: "eg-Settings".to_string(),
-                directory_invalid: false,
-                selected_file: PathBuf::new(),
-                original_file: EspansoYaml::default(),
-                edited_file: EspansoYaml::default(),
-                edited_file_te: Vec::new(),
-                original_config: ParsedConfig::default(),
-                edited_config: ParsedConfig::default(),
-                temp_word_separators: String::new(),
-                match_files: Vec::new(),
-                show_modal: false,
-                modal_title: String::new(),
-                modal_description: String::new(),
-                modal_ok_text: "OK".to_string(),
-                nav_queue: String::new(),
-                show_new_file_input: false,
-                new_file_name: String::new(),
-                file_name_change: String::new(),
-            }
+        }
+        EGUI {
+            espanso_loc: if valid_dir {
+                egui_data.espanso_dir.clone()
+            } else {
+                String::new()
+            },
+            recent_dirs,
+            software_renderer: egui_data.software_renderer,
+            present_mode: egui_data.present_mode.clone(),
+            antialiasing: egui_data.antialiasing,
+            high_contrast: egui_data.high_contrast,
+            browser_command: egui_data.browser_command.clone(),
+            accent_color: egui_data.accent_color.clone(),
+            large_file_threshold: egui_data.large_file_threshold,
+            match_counts: BTreeMap::new(),
+            max_file_size_mb: egui_data.max_file_size_mb,
+            unopenable_files: BTreeMap::new(),
+            preserve_empty_matches: egui_data.preserve_empty_matches,
+            compact_view: egui_data.compact_view,
+            file_compact_views: egui_data.file_compact_views.clone(),
+            always_multiline_replace: egui_data.always_multiline_replace,
+            warn_cursor_hints: egui_data.warn_cursor_hints,
+            block_scalar_replace: egui_data.block_scalar_replace,
+            multiline_replace: BTreeSet::new(),
+            compact_expanded: None,
+            round_trip_error: None,
+            selected_nav: "eg-Settings".to_string(),
+            directory_invalid: false,
+            selected_file: PathBuf::new(),
+            selected_file_modified: None,
+            original_file: EspansoYaml::default(),
+            edited_file: EspansoYaml::default(),
+            edited_file_te: Vec::new(),
+            quick_add_te: text_editor::Content::new(),
+            selected_matches: BTreeSet::new(),
+            dragging: None,
+            drag_target: None,
+            search_terms_input: BTreeMap::new(),
+            trigger_counts: BTreeMap::new(),
+            expanded_replace: None,
+            yaml_preview: None,
+            config_comparison: None,
+            validation_report: None,
+            extract_var: None,
+            lossy_file: None,
+            trigger_search: String::new(),
+            trigger_search_results: None,
+            expanded_search_file: None,
+            paste_list: None,
+            test_output: None,
+            governing_configs: Vec::new(),
+            show_global_vars: false,
+            global_vars_te: text_editor::Content::new(),
+            raw_match_edit: None,
+            global_vars_error: None,
+            // Filled in by the scan started in boot()
+            match_files: Vec::new(),
+            disabled_match_files: Vec::new(),
+            logs: text_editor::Content::new(),
+            scroll_offsets: BTreeMap::new(),
+            logs_loading: false,
+            scan_progress: None,
+            rescan_pending: false,
+            validation_progress: None,
+            pending_open: None,
+            original_config: ParsedConfig::default(),
+            edited_config: ParsedConfig::default(),
+            touched_config: BTreeSet::new(),
+            config_history: Vec::new(),
+            config_history_field: None,
+            temp_word_separators: String::new(),
+            config_presets: egui_data.config_presets.clone(),
+            preset_name: String::new(),
+            show_modal: false,
+            show_shortcuts: false,
+            nav_highlight: None,
+            modal_title: String::new(),
+            modal_description: String::new(),
+            modal_ok_text: "OK".to_string(),
+            modal_action: None,
+            modal_alt_action: None,
+            show_new_file_input: false,
+            new_file_name: String::new(),
+            new_file_folder: String::new(),
+            match_subfolders: Vec::new(),
+            file_name_change: String::new(),
+            onboarding: !valid_dir,
+            toast: load_warning,
+            auto_save: egui_data.auto_save,
+            auto_save_delay: egui_data.auto_save_delay,
+            last_activity: None,
+            auto_save_status: None,
+            recent_toasts: Vec::new(),
+            deleted_file: None,
         }
     }
 
@@ -197,65 +814,130 @@ impl EGUI {
     }
 
     pub fn theme(&self) -> Theme {
-        match dark_light::detect() {
-            dark_light::Mode::Light | dark_light::Mode::Default => Theme::Light,
-            dark_light::Mode::Dark => Theme::Dark,
+        let dark = matches!(dark_light::detect(), dark_light::Mode::Dark);
+        let accent = style::parse_accent(&self.accent_color);
+        match (self.high_contrast, accent, dark) {
+            (true, accent, dark) => style::high_contrast(dark, accent),
+            (false, Some(accent), dark) => style::with_accent(dark, accent),
+            (false, None, true) => Theme::Dark,
+            (false, None, false) => Theme::Light,
         }
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        keyboard::on_key_press(|key, modifiers| {
-            let keyboard::Key::Named(key) = key else {
-                return None;
-            };
+        let auto_save = if self.auto_save
+            && !self.selected_nav.starts_with("eg-")
+            && self.edited_file != self.original_file
+        {
+            Subscription::run_with_id("auto-save", every_second())
+        } else {
+            Subscription::none()
+        };
+        Subscription::batch([self.key_subscription(), auto_save])
+    }
 
-            match (key, modifiers) {
-                (key::Named::Tab, _) => Some(Message::TabPressed {
-                    shift: modifiers.shift(),
-                }),
+    fn key_subscription(&self) -> Subscription<Message> {
+        if self.dragging.is_some() {
+            // The button can be let go anywhere, not just over a card
+            return iced::event::listen_with(|event, _status, _window| match event {
+                iced::Event::Mouse(iced::mouse::Event::ButtonReleased(
+                    iced::mouse::Button::Left,
+                )) => Some(Message::DragReleased),
                 _ => None,
-            }
-        })
+            });
+        }
+        if self.show_modal {
+            return keyboard::on_key_press(|key, modifiers| shortcut_message(key, modifiers, true));
+        }
+        if self.show_shortcuts {
+            return keyboard::on_key_press(|key, _modifiers| match key.as_ref() {
+                keyboard::Key::Named(key::Named::Escape) | keyboard::Key::Character("?") => {
+                    Some(Message::ShortcutHelpToggled)
+                }
+                _ => None,
+            });
+        }
+
+        // Only gets keys no text field has captured
+        keyboard::on_key_press(|key, modifiers| shortcut_message(key, modifiers, false))
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            Message::ShowModal(title, description, destination) => {
-                self.modal_title = title;
-                self.modal_description = description;
-                self.nav_queue = destination;
-                self.show_modal = true;
+        if let Some(field) = message.config_field() {
+            self.touched_config.insert(field);
+        }
+        let config_step = message
+            .config_field()
+            .or(match message {
+                Message::ResetConfigPressed => Some("reset"),
+                Message::PresetPicked(_) => Some("preset"),
+                Message::UndoConfigPressed => Some("revert"),
+                _ => None,
+            })
+            .map(|field| (field, self.edited_config.clone()));
+        // Typing a trigger updates the counts itself; replace edits can't change them
+        let triggers_unchanged = matches!(
+            message,
+            Message::YamlInputChanged(..)
+                | Message::EditReplace(..)
+                | Message::ScanProgress(_)
+                | Message::ValidationProgress(_)
+        );
+        let activity = !matches!(
+            message,
+            Message::AutoSaveTick
+                | Message::ScanProgress(_)
+                | Message::ScanFinished(..)
+                | Message::MatchCountsFound(..)
+                | Message::ValidationProgress(_)
+                | Message::ValidationFinished(..)
+                | Message::LogsLoaded(_)
+        );
+        if activity {
+            self.last_activity = Some(Instant::now());
+            self.auto_save_status = None;
+        }
+        let task = self.handle_message(message);
+        if let Some((field, before)) = config_step {
+            if before != self.edited_config {
+                self.record_config_step(field, before);
+            }
+        }
+        if !triggers_unchanged {
+            self.count_triggers();
+        }
+        // Kept for the debug info, since toasts are how errors reach the user
+        if let Some(toast) = &self.toast {
+            if self.recent_toasts.last() != Some(toast) {
+                self.recent_toasts.push(toast.clone());
+                let excess = self.recent_toasts.len().saturating_sub(MAX_RECENT_TOASTS);
+                self.recent_toasts.drain(..excess);
             }
+        }
+        task
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ConfirmLeaveFile(action) => self.confirm_leave_file(action),
             Message::ModalOkPressed => {
-                self.show_modal = false;
-                if self.nav_queue == "eg-Delete" {
-                    // Delete self.selected_file
-                    match remove_file(self.selected_file.clone()) {
-                        Ok(_) => {}
-                        Err(err) => eprintln!("Failed to delete file: {}", err),
-                    }
-                    // Update file list
-                    self.match_files = get_all_match_file_stems(
-                        PathBuf::from(self.espanso_loc.clone()).join("match"),
-                    );
-                    // Navigate back to Settings
-                    self.nav_queue = String::new();
-                    self.modal_ok_text = "OK".to_string();
-                    let _ = self.update(Message::NavigateTo("eg-Settings".to_string()));
-                } else if !self.nav_queue.is_empty() {
-                    let destination = self.nav_queue.clone();
-                    self.nav_queue = String::new();
-                    let _ = self.update(Message::NavigateTo(destination));
-                }
-            }
-            Message::CloseModal => self.show_modal = false,
-            Message::ModalCancelPressed => {
-                self.show_modal = false;
-                self.modal_ok_text = "OK".to_string();
-                self.nav_queue = String::new();
+                let action = self.modal_action.take();
+                self.close_modal();
+                if let Some(action) = action {
+                    return self.perform_action(action);
+                }
             }
+            Message::ModalAltPressed => {
+                let action = self.modal_alt_action.take();
+                self.close_modal();
+                if let Some((_, action)) = action {
+                    return self.perform_action(action);
+                }
+            }
+            Message::CloseModal | Message::ModalCancelPressed => self.close_modal(),
             Message::AddPairPressed => {
                 self.edited_file.matches.push(YamlPairs::default());
+                self.edited_file_te.push(text_editor::Content::new());
                 return scrollable::snap_to(SCROLLABLE_ID.clone(), scrollable::RelativeOffset::END);
             }
             Message::EspansoDirInputChanged(value) => {
@@ -263,64 +945,127 @@ impl EGUI {
             }
             Message::YamlInputChanged(new_str, i, trig_repl) => {
                 if trig_repl == "trigger" {
-                    self.edited_file.matches.get_mut(i).unwrap().trigger = new_str;
+                    let a_match = self.edited_file.matches.get_mut(i).unwrap();
+                    let old_trigger = std::mem::replace(&mut a_match.trigger, new_str.clone());
+                    // Only the edited trigger changes, so adjust its counts
+                    // rather than recounting the whole file
+                    if let Some(count) = self.trigger_counts.get_mut(&old_trigger) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.trigger_counts.remove(&old_trigger);
+                        }
+                    }
+                    *self.trigger_counts.entry(new_str).or_default() += 1;
                 } else {
+                    // Keep the editor in step for when the card switches to it
+                    self.edited_file_te[i] = text_editor::Content::with_text(&new_str);
                     self.edited_file.matches.get_mut(i).unwrap().replace = new_str;
                 }
             }
             Message::NavigateTo(value) => {
+                // The folder can disappear while the app is open, e.g. an
+                // unmounted drive, which would leave these screens empty
+                if (value == "eg-Config" || !value.starts_with("eg-"))
+                    && !value.is_empty()
+                    && !Path::new(&value).is_absolute()
+                    && !valid_espanso_dir(self.espanso_loc.clone())
+                {
+                    self.directory_invalid = true;
+                    return self.update(Message::NavigateTo("eg-DirError".to_string()));
+                }
+                // Refuse before leaving the current view so nothing is lost
+                let mut opened = None;
+                if !value.is_empty() && !value.starts_with("eg-") {
+                    let path = self.match_file_path(&value);
+                    match check_match_file(&path, self.max_file_size_mb)
+                        .and_then(|_| open_match_file(&path, self.preserve_empty_matches))
+                    {
+                        Ok(read) => opened = Some(read),
+                        Err(reason) => {
+                            self.open_modal(
+                                "Can't Open File".to_string(),
+                                format!("{} {}", value, reason),
+                                "OK",
+                                None,
+                            );
+                            return Task::none();
+                        }
+                    }
+                }
                 self.selected_nav = value.clone();
+                self.nav_highlight = None;
+                self.validation_report = None;
                 let espanso_loc = self.espanso_loc.clone();
                 // Reset files to defaults
                 self.original_file = EspansoYaml::default();
                 self.edited_file = EspansoYaml::default();
+                self.selected_matches.clear();
+                self.search_terms_input.clear();
+                self.raw_match_edit = None;
+                self.multiline_replace.clear();
+                self.quick_add_te = text_editor::Content::new();
+                self.compact_expanded = None;
+                self.round_trip_error = None;
+                self.expanded_replace = None;
 
                 match value.as_str() {
                     "eg-Config" => {
                         self.selected_file = PathBuf::from(espanso_loc + "/config/default.yml");
-                        match ParsedConfig::load(&self.selected_file) {
-                            Ok(config) => {
+                        self.selected_file_modified = get_modified_time(&self.selected_file);
+                        match ParsedConfig::load_with_warning(&self.selected_file) {
+                            Ok((config, warning)) => {
                                 self.original_config = config;
-                                // Set combo list prefs to default if not set to prevent it
-                                // loooking like changes were made when they weren't
-                                if self.original_config.backend == None {
-                                    self.original_config.backend = Some("Auto".to_string());
-                                }
-                                if self.original_config.toggle_key == None {
-                                    self.original_config.toggle_key = Some("OFF".to_string());
+                                self.touched_config.clear();
+                                self.config_history.clear();
+                                self.config_history_field = None;
+                                if let Some(warning) = warning {
+                                    self.toast = Some(format!("default.yml {}", warning));
                                 }
-
                                 self.edited_config = self.original_config.clone();
                                 self.temp_word_separators =
-                                    if self.edited_config.word_separators.is_some() {
-                                        serde_json::to_string(
-                                            &self.edited_config.word_separators.clone().unwrap(),
-                                        )
-                                        .unwrap_or_default()
-                                    } else {
-                                        format!("{:?}", get_default_word_separators())
-                                    };
+                                    word_separators_text(&self.edited_config);
                             }
                             Err(e) => eprintln!("Error {:?}", e),
                         }
                     }
                     "eg-Settings" => self.selected_file = PathBuf::new(),
+                    "eg-DirError" => self.selected_file = PathBuf::new(),
                     "eg-About" => self.selected_file = PathBuf::new(),
+                    "eg-Logs" => {
+                        self.selected_file = PathBuf::new();
+                        return self.update(Message::RefreshLogsPressed);
+                    }
                     _ => {
-                        self.selected_file =
-                            PathBuf::from(espanso_loc + "/match/" + &self.selected_nav + ".yml");
-                        self.original_file = read_to_triggers(self.selected_file.clone());
-                        self.edited_file = self.original_file.clone();
-                        // copy matches to text_editor
-                        self.edited_file_te.clear();
-                        for a_match in self.edited_file.matches.clone() {
-                            self.edited_file_te
-                                .push(text_editor::Content::with_text(&a_match.replace));
+                        self.selected_file = self.match_file_path(&self.selected_nav);
+                        self.selected_file_modified = get_modified_time(&self.selected_file);
+                        let (file, warning, lossy) = opened.unwrap_or_default();
+                        if let Some(warning) = warning {
+                            self.toast = Some(format!("{} {}", self.selected_nav, warning));
                         }
-                        self.file_name_change = self.selected_nav.clone();
+                        self.lossy_file = lossy;
+                        self.original_file = file;
+                        self.edited_file = self.original_file.clone();
+                        self.sync_editors();
+                        self.show_global_vars = !self.edited_file.global_vars.is_empty();
+                        self.governing_configs =
+                            configs_including(&self.espanso_loc, &self.selected_file);
+                        self.file_name_change = split_stem(&self.selected_nav).1.to_string();
+                        return scrollable::snap_to(
+                            SCROLLABLE_ID.clone(),
+                            self.scroll_offsets
+                                .get(&self.selected_file)
+                                .copied()
+                                .unwrap_or(scrollable::RelativeOffset::START),
+                        );
                     }
                 }
             }
+            Message::FileScrolled(offset) => {
+                if !self.selected_file.as_os_str().is_empty() {
+                    self.scroll_offsets
+                        .insert(self.selected_file.clone(), offset);
+                }
+            }
             Message::BrowsePressed => {
                 let default_path_mac: PathBuf = ["Library", "Application Support", "espanso"]
                     .iter()
@@ -344,110 +1089,525 @@ impl EGUI {
                         self.espanso_loc = espanso_dir.into_os_string().into_string().unwrap();
                     } else {
                         self.directory_invalid = true;
+                        if !self.offer_to_create_default_config(&espanso_dir) {
+                            self.suggest_espanso_dir(&espanso_dir);
+                        }
                     }
                 }
             }
+            Message::DetectEspansoDirPressed => {
+                let default_dir = get_default_espanso_dir();
+                if valid_espanso_dir(default_dir.clone()) {
+                    self.espanso_loc = default_dir;
+                    return self.activate_espanso_dir();
+                } else {
+                    self.open_modal(
+                        "espanso folder not found".to_string(),
+                        format!(
+                            "No espanso folder was found at {}. Install espanso and run it once, or create a new folder.",
+                            default_dir
+                        ),
+                        "OK",
+                        None,
+                    );
+                }
+            }
+            Message::RetryEspansoDirPressed => {
+                if !Path::new(&self.espanso_loc).is_dir() {
+                    self.toast = Some(format!("{} still can't be found", self.espanso_loc));
+                    return Task::none();
+                }
+                return self.activate_espanso_dir();
+            }
+            Message::CreateEspansoDirPressed => {
+                if self.espanso_loc.trim().is_empty() {
+                    self.espanso_loc = get_default_espanso_dir();
+                }
+                match create_espanso_dir(Path::new(&self.espanso_loc)) {
+                    Ok(_) => return self.activate_espanso_dir(),
+                    Err(err) => self.open_modal(
+                        "Couldn't create folder".to_string(),
+                        format!("Failed to create {}: {}", self.espanso_loc, err),
+                        "OK",
+                        None,
+                    ),
+                }
+            }
             Message::SettingsSavePressed => {
-                if self.espanso_loc.ends_with("/") {
-                    self.espanso_loc = self.espanso_loc.trim_end_matches("/").to_string();
+                self.espanso_loc = normalize_dir_path(&self.espanso_loc, cfg!(windows));
+                return self.activate_espanso_dir();
+            }
+            Message::SoftwareRendererToggled(value) => {
+                self.software_renderer = value;
+                self.persist_egui_data();
+            }
+            Message::PresentModePicked(value) => {
+                self.present_mode = if value == PRESENT_MODE_OPTIONS[0] {
+                    None
+                } else {
+                    Some(value)
+                };
+                self.persist_egui_data();
+            }
+            Message::AntialiasingToggled(value) => {
+                self.antialiasing = value;
+                self.persist_egui_data();
+            }
+            Message::HighContrastToggled(value) => {
+                self.high_contrast = value;
+                self.persist_egui_data();
+            }
+            Message::AccentColorInput(value) => {
+                self.accent_color = value;
+                self.persist_egui_data();
+            }
+            Message::BrowserCommandInput(value) => {
+                self.browser_command = value;
+                self.persist_egui_data();
+            }
+            Message::LargeFileThresholdInput(value) => {
+                self.large_file_threshold = value;
+                self.persist_egui_data();
+            }
+            Message::MaxFileSizeInput(value) => {
+                self.max_file_size_mb = value;
+                self.persist_egui_data();
+                return self.refresh_match_files();
+            }
+            Message::PreserveEmptyMatchesToggled(value) => {
+                self.preserve_empty_matches = value;
+                self.persist_egui_data();
+            }
+            Message::AlwaysMultilineReplaceToggled(value) => {
+                self.always_multiline_replace = value;
+                self.persist_egui_data();
+            }
+            Message::WarnCursorHintsToggled(value) => {
+                self.warn_cursor_hints = value;
+                self.persist_egui_data();
+            }
+            Message::BlockScalarReplaceToggled(value) => {
+                self.block_scalar_replace = value;
+                self.persist_egui_data();
+            }
+            Message::FixCursorHintsPressed(index) => self.fix_cursor_hints(Some(index)),
+            Message::ReplaceNewlinePressed(i) => {
+                // Enter in the single-line field starts a second line in the editor
+                if let Some(a_match) = self.edited_file.matches.get(i) {
+                    let text = format!("{}\n", a_match.replace);
+                    let mut content = text_editor::Content::with_text(&text);
+                    let end = text_editor::Motion::DocumentEnd;
+                    content.perform(text_editor::Action::Move(end));
+                    self.edited_file_te[i] = content;
+                    self.multiline_replace.insert(i);
                 }
-                if valid_espanso_dir(self.espanso_loc.clone()) {
-                    self.directory_invalid = false;
-                    let new_egui_data = EGUIData {
-                        espanso_dir: self.espanso_loc.clone(),
-                    };
-                    let _ = write_egui_data(&new_egui_data);
-                    self.match_files = get_all_match_file_stems(
-                        PathBuf::from(self.espanso_loc.clone()).join("match"),
-                    )
+            }
+            Message::CompactViewToggled => {
+                // Files not switched yet open in whichever view was used last
+                self.compact_view = !self.is_compact_view();
+                self.file_compact_views
+                    .insert(self.selected_nav.clone(), self.compact_view);
+                self.compact_expanded = None;
+                self.round_trip_error = None;
+                self.persist_egui_data();
+            }
+            Message::CompactRowPressed(i) => {
+                self.compact_expanded = if self.compact_expanded == Some(i) {
+                    None
                 } else {
+                    Some(i)
+                };
+            }
+            Message::RecentDirPicked(value) => {
+                if valid_espanso_dir(value.clone()) {
+                    self.espanso_loc = value;
+                    return self.activate_espanso_dir();
+                } else if !self.offer_to_create_default_config(Path::new(&value)) {
+                    // Prune directories that were moved or deleted since last use
+                    self.recent_dirs.retain(|dir| *dir != value);
                     self.directory_invalid = true;
+                    self.persist_egui_data();
                 }
             }
             Message::ResetPressed => {
                 self.edited_file = self.original_file.clone();
-                self.edited_file_te.clear();
-                for a_match in self.edited_file.matches.clone() {
-                    self.edited_file_te
-                        .push(text_editor::Content::with_text(&a_match.replace));
-                }
+                self.selected_matches.clear();
+                self.search_terms_input.clear();
+                self.raw_match_edit = None;
+                self.multiline_replace.clear();
+                self.compact_expanded = None;
+                self.round_trip_error = None;
+                self.expanded_replace = None;
+                self.sync_editors();
             }
             Message::SaveFilePressed => {
-                let mut empty_lines = false;
-                for pairs in self.edited_file.matches.clone() {
-                    if pairs.trigger.trim().is_empty() || pairs.replace.trim().is_empty() {
-                        empty_lines = true;
-                        break;
-                    }
+                if !self.selected_file_under("match") && !self.is_external_file() {
+                    return Task::none();
                 }
-                if empty_lines {
-                    self.modal_title = "Empty Lines".to_string();
-                    self.modal_description = "No text boxes can be empty.".to_string();
-                    if !self.nav_queue.is_empty() {
-                        self.nav_queue = String::new();
-                    }
-                    self.show_modal = true;
+                if self.has_empty_fields() && !self.preserve_empty_matches {
+                    self.open_modal(
+                        "Empty Lines".to_string(),
+                        "No text boxes can be empty.".to_string(),
+                        "OK",
+                        None,
+                    );
+                } else if let Some(err) = &self.global_vars_error {
+                    self.open_modal(
+                        "Invalid Global Variables".to_string(),
+                        format!("The global variables aren't valid YAML: {}", err),
+                        "OK",
+                        None,
+                    );
+                } else if let Some(i) = self.first_changed_by_round_trip() {
+                    self.round_trip_error = Some(i);
+                    self.compact_expanded = Some(i);
+                    self.open_modal(
+                        "Match Wouldn't Save Correctly".to_string(),
+                        format!(
+                            "The match with trigger \"{}\" reads back differently once written as YAML, so saving would change it. Edit the highlighted match and try again.",
+                            self.edited_file.matches[i].trigger
+                        ),
+                        "OK",
+                        None,
+                    );
+                    return text_input::focus(trigger_input_id(i));
+                } else if self.warn_cursor_hints
+                    && self
+                        .edited_file
+                        .matches
+                        .iter()
+                        .any(|a_match| extra_cursor_hints(&a_match.replace) > 0)
+                {
+                    self.open_modal(
+                        "Extra Cursor Hints".to_string(),
+                        "Some replacements contain more than one $|$. espanso only moves the cursor to the first one and types the others out as text.".to_string(),
+                        "Keep First and Save",
+                        Some(PendingAction::FixCursorHintsAndSave),
+                    );
+                    self.modal_alt_action =
+                        Some(("Save As Is".to_string(), PendingAction::SaveWithCursorHints));
                 } else {
-                    write_from_triggers(self.selected_file.clone(), self.edited_file.clone());
-                    self.original_file = self.edited_file.clone();
+                    self.save_file_unless_modified();
                 }
             }
             Message::AddFilePressed => {
                 if self.show_new_file_input {
                     self.show_new_file_input = false;
                     self.new_file_name = String::new();
-                } else {
+                } else if !self.offer_to_recreate_match_dir() {
                     self.show_new_file_input = true;
+                    self.match_subfolders =
+                        get_match_subfolders(&PathBuf::from(&self.espanso_loc).join("match"));
+                    if !self.match_subfolders.contains(&self.new_file_folder) {
+                        self.new_file_folder = String::new();
+                    }
                 }
             }
             Message::NewFileInputChanged(value) => self.new_file_name = value,
+            Message::NewFileFolderPicked(value) => {
+                self.new_file_folder = if value == NEW_FILE_ROOT_FOLDER {
+                    String::new()
+                } else {
+                    value
+                };
+            }
             Message::SubmitNewFileName => {
                 self.show_new_file_input = false;
                 if !self.new_file_name.trim().is_empty() {
                     if self.new_file_name.ends_with(".yml") {
                         self.new_file_name = self.new_file_name.trim_end_matches(".yml").to_string()
                     }
-                    create_new_yml_file(PathBuf::from(
-                        self.espanso_loc.clone() + "/match/" + &self.new_file_name + ".yml",
-                    ));
-                    self.match_files = get_all_match_file_stems(
-                        PathBuf::from(self.espanso_loc.clone()).join("match"),
-                    );
+                    let new_stem = join_stem(&self.new_file_folder, &self.new_file_name);
+                    let new_file = match_file_path(&self.espanso_loc, &new_stem, false);
+                    if !is_valid_file_name(&self.new_file_name) {
+                        self.open_modal(
+                            "Invalid File Name".to_string(),
+                            "File names may only contain letters, numbers, spaces, dashes, underscores and dots. They can't start with a dot, end with a dot or space, or be a name reserved by Windows such as CON or NUL.".to_string(),
+                            "OK",
+                            None,
+                        );
+                    } else if self.offer_to_recreate_match_dir() {
+                        // Keep the typed name for once the folder is back
+                        return Task::none();
+                    } else if new_file.exists() {
+                        self.open_modal(
+                            "File Exists".to_string(),
+                            format!("{}.yml already exists.", self.new_file_name),
+                            "OK",
+                            None,
+                        );
+                    } else {
+                        create_new_yml_file(new_file);
+                        self.match_files.push(new_stem);
+                        self.new_file_name = String::new();
+                        return self.refresh_match_files();
+                    }
                     self.new_file_name = String::new();
                 }
             }
+            Message::DragStarted(index) => {
+                self.dragging = Some(index);
+                self.drag_target = Some(index);
+            }
+            Message::DragHovered(index) => {
+                if self.dragging.is_some() {
+                    self.drag_target = Some(index);
+                }
+            }
+            Message::DragReleased => {
+                if let (Some(from), Some(to)) = (self.dragging.take(), self.drag_target.take()) {
+                    if from != to {
+                        return self.update(Message::MoveRow { from, to });
+                    }
+                }
+            }
+            Message::MoveRow { from, to } => {
+                let len = self.edited_file.matches.len();
+                if from < len && to < len {
+                    self.edited_file.move_match(from, to);
+                    let editor = self.edited_file_te.remove(from);
+                    self.edited_file_te.insert(to, editor);
+                    self.selected_matches.clear();
+                    self.search_terms_input.clear();
+                    self.multiline_replace.clear();
+                    self.compact_expanded = None;
+                    self.round_trip_error = None;
+                    self.expanded_replace = None;
+                }
+            }
+            Message::SortPicked(order) => {
+                let descending = order == SORT_OPTIONS[1];
+                let mut order: Vec<usize> = (0..self.edited_file.matches.len()).collect();
+                order.sort_by_cached_key(|i| self.edited_file.matches[*i].trigger.to_lowercase());
+                if descending {
+                    order.reverse();
+                }
+                // Move each editor along with its match so they stay paired
+                self.edited_file.reorder_matches(&order);
+                let mut editors: Vec<Option<text_editor::Content>> =
+                    std::mem::take(&mut self.edited_file_te)
+                        .into_iter()
+                        .map(Some)
+                        .collect();
+                self.edited_file_te = order.iter().filter_map(|i| editors[*i].take()).collect();
+                self.selected_matches.clear();
+                self.search_terms_input.clear();
+                self.multiline_replace.clear();
+                self.compact_expanded = None;
+                self.round_trip_error = None;
+                self.expanded_replace = None;
+            }
+            Message::FormatFilePressed => {
+                if self.edited_file != self.original_file {
+                    self.open_modal(
+                        "Unsaved Changes".to_string(),
+                        "Save or reset your changes before formatting the file.".to_string(),
+                        "OK",
+                        None,
+                    );
+                    return Task::none();
+                }
+                let has_comments = std::fs::read_to_string(&self.selected_file)
+                    .is_ok_and(|yaml| yaml.lines().any(|line| line.trim_start().starts_with('#')));
+                self.open_modal(
+                    "Format file?".to_string(),
+                    if has_comments {
+                        "This rewrites the whole file with consistent indentation and key order. The comments in this file will be removed."
+                    } else {
+                        "This rewrites the whole file with consistent indentation and key order."
+                    }
+                    .to_string(),
+                    "Format",
+                    Some(PendingAction::FormatFile),
+                );
+            }
+            Message::DuplicateFilePressed => {
+                if self.edited_file != self.original_file {
+                    self.open_modal(
+                        "Unsaved Changes".to_string(),
+                        "The copy is made from the saved file, and opening it will erase any unsaved changes here.".to_string(),
+                        "Duplicate",
+                        Some(PendingAction::DuplicateFile),
+                    );
+                } else {
+                    return self.perform_action(PendingAction::DuplicateFile);
+                }
+            }
+            Message::ExportPackagePressed => {
+                let Some(folder) = FileDialog::new()
+                    .set_title("Choose where to create the package")
+                    .pick_folder()
+                else {
+                    return Task::none();
+                };
+                let name = package_name_for(&if self.is_external_file() {
+                    self.selected_file
+                        .file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string()
+                } else {
+                    split_stem(&self.selected_nav).1.to_string()
+                });
+                let package_dir = folder.join(&name).join("0.1.0");
+                if package_dir.exists() {
+                    self.open_modal(
+                        "Package Exists".to_string(),
+                        format!("{} already exists.", package_dir.display()),
+                        "OK",
+                        None,
+                    );
+                } else {
+                    match scaffold_package(
+                        &package_dir,
+                        &name,
+                        &self.edited_file,
+                        self.block_scalar_replace,
+                    ) {
+                        Ok(_) => {
+                            self.toast = Some(format!(
+                                "Created package {} in {}. Fill in _manifest.yml before publishing.",
+                                name,
+                                package_dir.display()
+                            ))
+                        }
+                        Err(err) => {
+                            eprintln!("Failed to create package: {}", err);
+                            self.toast = Some(format!("Couldn't create package: {}", err));
+                        }
+                    }
+                }
+            }
             Message::FileNameChangeInputChanged(value) => {
-                if is_valid_file_name(&value.clone()) {
+                if has_valid_file_name_chars(&value) {
                     self.file_name_change = value;
                 }
             }
             Message::FileNameChangeSubmit => {
-                if self.file_name_change != self.selected_nav
-                    && is_valid_file_name(&self.file_name_change)
-                {
-                    let match_path = PathBuf::from(self.espanso_loc.clone()).join("match");
-                    let from_path = match_path.join(format!("{}.yml", self.selected_nav));
-                    let to_path = match_path.join(format!("{}.yml", self.file_name_change));
+                let (folder, name) = split_stem(&self.selected_nav);
+                let new_stem = join_stem(folder, &self.file_name_change);
+                if self.file_name_change != name && !is_valid_file_name(&self.file_name_change) {
+                    self.open_modal(
+                        "Invalid File Name".to_string(),
+                        "File names can't start with a dot, end with a dot or space, or be a name reserved by Windows such as CON or NUL.".to_string(),
+                        "OK",
+                        None,
+                    );
+                } else if self.file_name_change != name {
+                    let from_path = self.match_file_path(&self.selected_nav);
+                    let to_path = match_file_path(
+                        &self.espanso_loc,
+                        &new_stem,
+                        self.disabled_match_files.contains(&self.selected_nav),
+                    );
+                    if to_path.exists() {
+                        self.open_modal(
+                            "File Exists".to_string(),
+                            format!("{}.yml already exists.", self.file_name_change),
+                            "OK",
+                            None,
+                        );
+                        return Task::none();
+                    }
                     match rename(from_path, to_path.clone()) {
                         Ok(_) => {}
                         Err(err) => eprintln!("Failed to rename file: {}", err),
                     }
 
                     // Refresh file list
-                    self.match_files = get_all_match_file_stems(match_path);
+                    for file in self
+                        .match_files
+                        .iter_mut()
+                        .chain(self.disabled_match_files.iter_mut())
+                    {
+                        if *file == self.selected_nav {
+                            *file = new_stem.clone();
+                        }
+                    }
+
+                    if let Some(compact) = self.file_compact_views.remove(&self.selected_nav) {
+                        self.file_compact_views.insert(new_stem.clone(), compact);
+                        self.persist_egui_data();
+                    }
 
                     // Set necessary variables to new name
-                    self.selected_nav = self.file_name_change.clone();
+                    self.selected_nav = new_stem;
                     self.selected_file = to_path;
+                    return self.refresh_match_files();
+                }
+            }
+            Message::FileEnabledToggled(enabled) => {
+                let from_path = self.match_file_path(&self.selected_nav);
+                let to_path = match_file_path(&self.espanso_loc, &self.selected_nav, !enabled);
+                if to_path.exists() {
+                    self.open_modal(
+                        "File already exists".to_string(),
+                        format!(
+                            "Can't {} {} because {} already exists.",
+                            if enabled { "enable" } else { "disable" },
+                            self.selected_nav,
+                            to_path.display()
+                        ),
+                        "OK",
+                        None,
+                    );
+                } else {
+                    if let Some(parent) = to_path.parent() {
+                        if let Err(err) = create_dir_all(parent) {
+                            eprintln!("Failed to create directory: {}", err);
+                        }
+                    }
+                    match rename(&from_path, &to_path) {
+                        Ok(_) => {
+                            self.selected_file = to_path;
+                            let stem = self.selected_nav.clone();
+                            self.match_files.retain(|file| *file != stem);
+                            self.disabled_match_files.retain(|file| *file != stem);
+                            if enabled {
+                                self.match_files.push(stem);
+                            } else {
+                                self.disabled_match_files.push(stem);
+                            }
+                        }
+                        Err(err) => eprintln!("Failed to move file: {}", err),
+                    }
+                    return self.refresh_match_files();
+                }
+            }
+            Message::OpenExternalFilePressed => {
+                let Some(path) = FileDialog::new()
+                    .set_title("Open a match file")
+                    .add_filter("YAML", &["yml"])
+                    .set_directory(&self.espanso_loc)
+                    .pick_file()
+                else {
+                    return Task::none();
+                };
+                // Files in this espanso folder open as usual, anything else by its full path
+                let destination = match locate_espanso_file(&path) {
+                    Some((espanso_dir, destination))
+                        if Path::new(&espanso_dir) == Path::new(&self.espanso_loc) =>
+                    {
+                        destination
+                    }
+                    _ => std::path::absolute(&path)
+                        .unwrap_or(path)
+                        .display()
+                        .to_string(),
+                };
+                if self.unsaved_changes() {
+                    self.confirm_leave_file(PendingAction::Navigate(destination));
+                } else {
+                    return self.update(Message::NavigateTo(destination));
                 }
             }
             Message::DeleteFilePressed => {
-                self.modal_title = "Delete file?".to_string();
-                self.modal_description =
-                    "Are you sure you want to delete the file? This cannot be undone.".to_string();
-                self.modal_ok_text = "Delete".to_string();
-                self.nav_queue = "eg-Delete".to_string();
-                self.show_modal = true;
+                self.open_modal(
+                    "Delete file?".to_string(),
+                    "Are you sure you want to delete the file? This cannot be undone.".to_string(),
+                    "Delete",
+                    Some(PendingAction::DeleteFile),
+                );
             }
             Message::BackendPicked(value) => self.edited_config.backend = Some(value),
             Message::EnableToggled(value) => self.edited_config.enable = Some(value),
@@ -460,6 +1620,10 @@ impl EGUI {
             Message::PasteShortcutInput(value) => self.edited_config.paste_shortcut = Some(value),
             Message::SearchShortcutInput(value) => self.edited_config.search_shortcut = Some(value),
             Message::SearchTriggerInput(value) => self.edited_config.search_trigger = Some(value),
+            // An empty label is left out of the file rather than written as ''
+            Message::LabelInput(value) => {
+                self.edited_config.label = (!value.trim().is_empty()).then_some(value)
+            }
             Message::PrePasteDelayInput(value) => self.edited_config.pre_paste_delay = Some(value),
             Message::X11FastInjectToggled(value) => {
                 self.edited_config.disable_x11_fast_inject = Some(value)
@@ -483,9 +1647,7 @@ impl EGUI {
             Message::BackspaceLimitInput(value) => self.edited_config.backspace_limit = Some(value),
             Message::ApplyPatchToggled(value) => self.edited_config.apply_patch = Some(value),
             Message::KeyboardLayoutInput(value) => {
-                let json_string = format!("{{ \"layout\": \"{}\" }}", value);
-                let map: BTreeMap<String, String> = serde_json::from_str(&json_string).unwrap();
-                self.edited_config.keyboard_layout = Some(map);
+                set_keyboard_layout(&mut self.edited_config, value)
             }
             Message::UndoBackspaceToggled(value) => self.edited_config.undo_backspace = Some(value),
             Message::ShowNotificationsToggled(value) => {
@@ -502,71 +1664,268 @@ impl EGUI {
                 self.edited_config.win32_keyboard_layout_cache_interval = Some(value)
             }
             Message::SaveConfigPressed => {
-                let word_separators_changed = self.temp_word_separators.to_owned()
-                    != if self.edited_config.word_separators.is_some() {
-                        serde_json::to_string(&self.edited_config.word_separators.clone().unwrap())
-                            .unwrap_or_default()
-                    } else {
-                        format!("{:?}", get_default_word_separators())
-                    };
-                if word_separators_changed {
-                    let mut corrected_string = self.temp_word_separators.clone();
-                    if !corrected_string.contains("\\\\r") {
-                        corrected_string = corrected_string.replace("\\r", "\\\\r");
-                    }
-
-                    if !corrected_string.contains("\\\\n") {
-                        corrected_string = corrected_string.replace("\\n", "\\\\n");
-                    }
-
-                    if !corrected_string.contains("\\\\u0016") {
-                        corrected_string = corrected_string.replace("\\u{16}", "\\\\u0016");
-                    }
-
-                    match serde_json::from_str::<Vec<String>>(&corrected_string) {
-                        Ok(value) => {
-                            self.edited_config.word_separators = Some(value);
-                        }
-                        Err(err) => eprintln!("Couldn't parse WS: {}", err),
-                    };
+                if !self.selected_file_under("config") {
+                    return Task::none();
                 }
-
-                overwrite_config(&self.selected_file.clone(), &self.edited_config.clone());
-                self.original_config = self.edited_config.clone();
-                self.temp_word_separators = if self.edited_config.word_separators.is_some() {
-                    serde_json::to_string(&self.edited_config.word_separators.clone().unwrap())
-                        .unwrap_or_default()
+                if let Err(err) = parse_word_separators(&self.temp_word_separators) {
+                    self.show_word_separators_error(err);
+                } else if let Some(colliding) = self
+                    .edited_config
+                    .search_trigger
+                    .as_deref()
+                    .map(|trigger| {
+                        files_with_trigger(&self.espanso_loc, &self.match_files, trigger)
+                    })
+                    .filter(|files| !files.is_empty())
+                {
+                    self.open_modal(
+                        "Search Trigger In Use".to_string(),
+                        format!(
+                            "The search trigger \"{}\" is also a match trigger in {}. Typing it will do one or the other unpredictably. Save anyway?",
+                            self.edited_config.search_trigger.clone().unwrap_or_default(),
+                            colliding.join(", ")
+                        ),
+                        "Save Anyway",
+                        Some(PendingAction::SaveConfigAnyway),
+                    );
+                } else if config_disables_ui(&self.edited_config) {
+                    self.open_modal(
+                        "No Way Back In".to_string(),
+                        "With the toggle key off and both the search shortcut and search trigger disabled, there will be no keyboard way to toggle espanso or open its search. Save anyway?".to_string(),
+                        "Save Anyway",
+                        Some(PendingAction::SaveConfigAnyway),
+                    );
+                } else if self.modified_since_load() {
+                    self.show_modified_modal(PendingAction::OverwriteConfig);
                 } else {
-                    format!("{:?}", get_default_word_separators())
-                };
+                    self.save_config();
+                }
+            }
+            Message::UndoConfigStepPressed => {
+                if self.selected_nav != "eg-Config" {
+                    return Task::none();
+                }
+                if let Some((_, before)) = self.config_history.pop() {
+                    if before.word_separators != self.edited_config.word_separators {
+                        self.temp_word_separators = word_separators_text(&before);
+                    }
+                    self.edited_config = before;
+                    self.config_history_field = None;
+                }
             }
             Message::ResetConfigPressed => {
                 self.edited_config = ParsedConfig::default();
-                self.temp_word_separators = if self.edited_config.word_separators.is_some() {
-                    serde_json::to_string(&self.edited_config.word_separators.clone().unwrap())
-                        .unwrap_or_default()
-                } else {
-                    format!("{:?}", get_default_word_separators())
-                };
-                // Reset combo list prefs to default to prevent it
-                // loooking like changes were made when they weren't
-                self.edited_config.backend = Some("Auto".to_string());
-                self.edited_config.toggle_key = Some("OFF".to_string());
+                self.temp_word_separators = word_separators_text(&self.edited_config);
             }
             Message::UndoConfigPressed => {
                 self.edited_config = self.original_config.clone();
-                self.temp_word_separators = if self.edited_config.word_separators.is_some() {
-                    serde_json::to_string(&self.edited_config.word_separators.clone().unwrap())
-                        .unwrap_or_default()
-                } else {
-                    format!("{:?}", get_default_word_separators())
-                };
+                self.touched_config.clear();
+                self.temp_word_separators = word_separators_text(&self.edited_config);
             }
-            Message::LaunchURL(value) => open_link(&value),
-            Message::DeleteRowPressed(index) => {
-                self.edited_file.matches.remove(index);
+            Message::PresetPicked(name) => {
+                let preset = builtin_presets()
+                    .into_iter()
+                    .find(|(builtin, _)| *builtin == name)
+                    .map(|(_, preset)| preset)
+                    .or_else(|| self.config_presets.get(&name).cloned());
+                if let Some(preset) = preset {
+                    // Only fills in the editor; the user reviews it and saves
+                    self.edited_config = apply_preset(&self.edited_config, preset);
+                    self.temp_word_separators = word_separators_text(&self.edited_config);
+                    if self.config_presets.contains_key(&name) {
+                        self.preset_name = name.clone();
+                    }
+                    self.toast = Some(format!("Applied preset \"{}\". Save to keep it.", name));
+                }
             }
-            Message::EditReplace(action, i) => match action {
+            Message::PresetNameInput(value) => self.preset_name = value,
+            Message::SavePresetPressed => {
+                let name = self.preset_name.trim().to_string();
+                self.config_presets
+                    .insert(name.clone(), without_file_options(self.config_to_write()));
+                self.persist_egui_data();
+                self.toast = Some(format!("Saved preset \"{}\"", name));
+            }
+            Message::DeletePresetPressed => {
+                if self
+                    .config_presets
+                    .remove(self.preset_name.trim())
+                    .is_some()
+                {
+                    self.persist_egui_data();
+                    self.preset_name.clear();
+                }
+            }
+            Message::LaunchURL(value) => open_link(&value, &self.browser_command),
+            Message::DeleteRowPressed(index) => {
+                self.edited_file.remove_match(index);
+                self.edited_file_te.remove(index);
+                self.search_terms_input.clear();
+                self.multiline_replace.clear();
+                self.compact_expanded = None;
+                self.round_trip_error = None;
+                // Shift the selection down past the removed row
+                self.selected_matches = self
+                    .selected_matches
+                    .iter()
+                    .filter(|i| **i != index)
+                    .map(|i| if *i > index { i - 1 } else { *i })
+                    .collect();
+            }
+            Message::InsertRow(index) => {
+                let index = index.min(self.edited_file.matches.len());
+                self.edited_file.insert_match(index, YamlPairs::default());
+                self.edited_file_te.insert(index, text_editor::Content::new());
+                self.search_terms_input.clear();
+                self.multiline_replace.clear();
+                self.compact_expanded = None;
+                self.round_trip_error = None;
+                // Shift the selection up past the new row
+                self.selected_matches = self
+                    .selected_matches
+                    .iter()
+                    .map(|i| if *i >= index { i + 1 } else { *i })
+                    .collect();
+                let last = self.edited_file.matches.len().saturating_sub(1).max(1);
+                return Task::batch([
+                    scrollable::snap_to(
+                        SCROLLABLE_ID.clone(),
+                        scrollable::RelativeOffset {
+                            x: 0.0,
+                            y: index as f32 / last as f32,
+                        },
+                    ),
+                    text_input::focus(trigger_input_id(index)),
+                ]);
+            }
+            Message::MatchSelected(index, selected) => {
+                if selected {
+                    self.selected_matches.insert(index);
+                } else {
+                    self.selected_matches.remove(&index);
+                }
+            }
+            Message::ClearSelectionPressed => self.selected_matches.clear(),
+            Message::ExtractVarPressed => {
+                let replaces: Vec<&str> = self
+                    .selected_matches
+                    .iter()
+                    .filter_map(|i| self.edited_file.matches.get(*i))
+                    .map(|a_match| a_match.replace.as_str())
+                    .collect();
+                let shared = longest_common_substring(&replaces);
+                // Anything shorter isn't worth a variable; let the user type it
+                let shared = if shared.chars().count() < 3 {
+                    String::new()
+                } else {
+                    shared
+                };
+                let name = var_name_for(&shared);
+                self.extract_var = Some((shared, name));
+            }
+            Message::ExtractVarTextInput(value) => {
+                if let Some((shared, _)) = self.extract_var.as_mut() {
+                    *shared = value;
+                }
+            }
+            Message::ExtractVarNameInput(value) => {
+                if let Some((_, name)) = self.extract_var.as_mut() {
+                    *name = value;
+                }
+            }
+            Message::ApplyExtractVarPressed => {
+                let Some((shared, name)) = self.extract_var.take() else {
+                    return Task::none();
+                };
+                let placeholder = format!("{{{{{}}}}}", name);
+                let mut changed = 0;
+                for i in &self.selected_matches {
+                    if let Some(a_match) = self.edited_file.matches.get_mut(*i) {
+                        if a_match.replace.contains(&shared) {
+                            a_match.replace = a_match.replace.replace(&shared, &placeholder);
+                            self.edited_file_te[*i] =
+                                text_editor::Content::with_text(&a_match.replace);
+                            changed += 1;
+                        }
+                    }
+                }
+                let mut params = serde_yaml::Mapping::new();
+                params.insert("echo".into(), shared.into());
+                let mut var = serde_yaml::Mapping::new();
+                var.insert("name".into(), name.clone().into());
+                var.insert("type".into(), "echo".into());
+                var.insert("params".into(), params.into());
+                self.edited_file.global_vars.push(var.into());
+                self.global_vars_te = text_editor::Content::with_text(
+                    &serde_yaml::to_string(&self.edited_file.global_vars).unwrap_or_default(),
+                );
+                self.global_vars_error = None;
+                self.show_global_vars = true;
+                let toast = format!(
+                    "Replaced the text with {} in {} match(es)",
+                    placeholder, changed
+                );
+                self.toast = Some(toast);
+            }
+            Message::CloseExtractVar => self.extract_var = None,
+            Message::CopySelectedYamlPressed => {
+                self.toast = Some(format!(
+                    "Copied {} match(es) as YAML",
+                    self.selected_matches.len()
+                ));
+                return iced::clipboard::write(EspansoYaml::to_snippet(&self.selected_pairs()));
+            }
+            Message::DeleteSelectedPressed => {
+                self.open_modal(
+                    "Delete matches?".to_string(),
+                    format!(
+                        "Are you sure you want to delete {} selected match(es)?",
+                        self.selected_matches.len()
+                    ),
+                    "Delete",
+                    Some(PendingAction::DeleteSelectedMatches),
+                );
+            }
+            Message::ImportPackTo(pack, destination) => {
+                let path = self.match_file_path(&destination);
+                let imported = EspansoYaml::from_snippet(SNIPPET_PACKS[pack].yaml)
+                    .map_err(|err| err.to_string())
+                    .and_then(|pack_file| {
+                        let (yaml, _) = read_yaml_text(&path).map_err(|err| err.to_string())?;
+                        let mut file = EspansoYaml::parse(&yaml, self.preserve_empty_matches)
+                            .map_err(|err| err.to_string())?;
+                        let counts = file.append(pack_file);
+                        write_from_triggers(path.clone(), file, self.block_scalar_replace)
+                            .map_err(|err| err.to_string())?;
+                        Ok(counts)
+                    });
+                match imported {
+                    Ok((added, skipped)) => {
+                        self.toast = Some(if skipped > 0 {
+                            format!(
+                                "Added {} match(es) to {}, skipped {} whose trigger was already used",
+                                added, destination, skipped
+                            )
+                        } else {
+                            format!("Added {} match(es) to {}", added, destination)
+                        });
+                        if let Some(count) = self.match_counts.get_mut(&destination) {
+                            *count += added;
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to import snippet pack: {}", err);
+                        self.open_modal(
+                            "Couldn't Add Snippets".to_string(),
+                            format!("{} couldn't be updated: {}", destination, err),
+                            "OK",
+                            None,
+                        );
+                    }
+                }
+            }
+            Message::MoveSelectedTo(destination) => self.move_selected_to(destination, false),
+            Message::EditReplace(action, i) => match action {
                 text_editor::Action::Scroll { lines: _ } => {}
                 action => {
                     let is_edit = action.is_edit();
@@ -585,6 +1944,630 @@ impl EGUI {
                     }
                 }
             },
+            Message::ExternalEditPressed(i) => {
+                if let Some(pair) = self.edited_file.matches.get(i) {
+                    let replace = pair.replace.clone();
+                    self.toast = Some(format!(
+                        "Editing {} externally. Save and close the editor to bring the text back.",
+                        pair.trigger
+                    ));
+                    return Task::perform(
+                        edit_in_external_editor(replace.clone()),
+                        move |result| Message::ExternalEditFinished(i, replace.clone(), result),
+                    );
+                }
+            }
+            Message::ExternalEditFinished(i, sent, result) => match result {
+                // Only apply it if the match wasn't changed or moved in the meantime
+                Ok(edited) => match self.edited_file.matches.get_mut(i) {
+                    Some(pair) if pair.replace == sent => {
+                        // Editors on Windows may have switched to CRLF
+                        let edited = edited.replace("\r\n", "\n");
+                        pair.replace = edited.trim_end_matches('\n').to_string();
+                        self.edited_file_te[i] = text_editor::Content::with_text(&pair.replace);
+                        self.toast = Some(format!("Updated {} from the editor", pair.trigger));
+                    }
+                    _ => {
+                        self.toast = Some(
+                            "The match changed while it was open in the editor, so the edit wasn't applied."
+                                .to_string(),
+                        )
+                    }
+                },
+                Err(err) => {
+                    eprintln!("External edit failed: {}", err);
+                    self.open_modal("Couldn't Edit Externally".to_string(), err, "OK", None);
+                }
+            },
+            Message::CopyMatchYamlPressed(index) => {
+                if let Some(pair) = self.edited_file.matches.get(index) {
+                    self.toast = Some(format!("Copied {} as YAML", pair.trigger));
+                    return iced::clipboard::write(EspansoYaml::to_snippet(std::slice::from_ref(
+                        pair,
+                    )));
+                }
+            }
+            Message::CopyTriggerPressed(index) => {
+                if let Some(pair) = self.edited_file.matches.get(index) {
+                    self.toast = Some(format!("Copied trigger {}", pair.trigger));
+                    return iced::clipboard::write(pair.trigger.clone());
+                }
+            }
+            Message::CopyReplacePressed(index) => {
+                if let Some(pair) = self.edited_file.matches.get(index) {
+                    self.toast = Some(format!("Copied replace text of {}", pair.trigger));
+                    return iced::clipboard::write(pair.replace.clone());
+                }
+            }
+            Message::PasteYamlPressed => {
+                return iced::clipboard::read().map(Message::PasteYamlReceived);
+            }
+            Message::PasteYamlReceived(contents) => {
+                match EspansoYaml::from_snippet(&contents.unwrap_or_default()) {
+                    Ok(pasted) => {
+                        let added = pasted.matches.len() + pasted.preserved_matches.len();
+                        for (_, value) in pasted.preserved_matches {
+                            self.edited_file
+                                .preserved_matches
+                                .push((self.edited_file.matches.len(), value));
+                        }
+                        for pair in pasted.matches {
+                            self.edited_file_te
+                                .push(text_editor::Content::with_text(&pair.replace));
+                            self.edited_file.matches.push(pair);
+                        }
+                        self.toast = Some(format!("Pasted {} match(es)", added));
+                    }
+                    Err(_) => {
+                        self.toast =
+                            Some("The clipboard doesn't contain espanso matches".to_string())
+                    }
+                }
+            }
+            Message::PasteListPressed => {
+                return iced::clipboard::read().map(Message::PasteListReceived);
+            }
+            Message::PasteListReceived(contents) => {
+                let (pairs, triggers) = parse_match_list(&contents.unwrap_or_default());
+                if pairs.is_empty() && triggers.is_empty() {
+                    self.toast = Some("The clipboard doesn't contain any triggers".to_string());
+                } else if triggers.is_empty() {
+                    let (added, skipped) = self.append_pasted_pairs(pairs);
+                    self.toast = Some(pasted_list_toast(added, skipped));
+                } else {
+                    // Lines with their own replace don't need to wait for the prompt
+                    if !pairs.is_empty() {
+                        let (added, skipped) = self.append_pasted_pairs(pairs);
+                        self.toast = Some(pasted_list_toast(added, skipped));
+                    }
+                    self.paste_list = Some((triggers, String::new()));
+                }
+            }
+            Message::PasteListReplaceInput(value) => {
+                if let Some((_, replace)) = self.paste_list.as_mut() {
+                    *replace = value;
+                }
+            }
+            Message::ApplyPasteListPressed => {
+                if let Some((triggers, replace)) = self.paste_list.take() {
+                    let pairs = triggers
+                        .into_iter()
+                        .map(|trigger| (trigger, replace.clone()))
+                        .collect();
+                    let (added, skipped) = self.append_pasted_pairs(pairs);
+                    self.toast = Some(pasted_list_toast(added, skipped));
+                }
+            }
+            Message::ClosePasteList => self.paste_list = None,
+            Message::TriggerSearchInput(value) => {
+                self.trigger_search = value;
+                if self.trigger_search.trim().is_empty() {
+                    self.trigger_search_results = None;
+                    self.expanded_search_file = None;
+                }
+            }
+            Message::TriggerSearchSubmit => self.run_trigger_search(),
+            Message::SearchResultFileToggled(stem) => {
+                self.expanded_search_file = if self.expanded_search_file.as_ref() == Some(&stem) {
+                    None
+                } else {
+                    Some(stem)
+                };
+            }
+            Message::DismissToast => {
+                self.toast = None;
+                self.deleted_file = None;
+            }
+            Message::UndoDeletePressed => {
+                if let Some((stem, disabled, contents)) = self.deleted_file.take() {
+                    let path = match_file_path(&self.espanso_loc, &stem, disabled);
+                    if path.exists() {
+                        let toast =
+                            format!("Couldn't restore {}: a file with that name exists", stem);
+                        self.toast = Some(toast);
+                        return Task::none();
+                    }
+                    let restored = path
+                        .parent()
+                        .map_or(Ok(()), create_dir_all)
+                        .and_then(|_| std::fs::write(&path, contents));
+                    match restored {
+                        Ok(_) => {
+                            if disabled {
+                                self.disabled_match_files.push(stem.clone());
+                            } else {
+                                self.match_files.push(stem.clone());
+                            }
+                            self.toast = Some(format!("Restored {}", stem));
+                            let scan = self.refresh_match_files();
+                            return Task::batch([scan, self.update(Message::NavigateTo(stem))]);
+                        }
+                        Err(err) => {
+                            eprintln!("Failed to restore file: {}", err);
+                            self.toast = Some(format!("Couldn't restore {}: {}", stem, err));
+                        }
+                    }
+                }
+            }
+            Message::ExportAllPressed => {
+                let export_file = FileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .set_file_name("espanso-export.json")
+                    .save_file();
+                if let Some(export_file) = export_file {
+                    let result = export_espanso_dir(Path::new(&self.espanso_loc))
+                        .and_then(|json| Ok(std::fs::write(&export_file, json)?));
+                    self.toast = Some(match result {
+                        Ok(_) => format!("Exported to {}", export_file.display()),
+                        Err(err) => format!("Export failed: {}", err),
+                    });
+                }
+            }
+            Message::GlobalVarsToggled => self.show_global_vars = !self.show_global_vars,
+            Message::EditRawMatchPressed(j) => {
+                if let Some((_, value)) = self.edited_file.preserved_matches.get(j) {
+                    let yaml = serde_yaml::to_string(value).unwrap_or_default();
+                    self.raw_match_edit = Some((j, text_editor::Content::with_text(&yaml)));
+                }
+            }
+            Message::RawMatchAction(action) => {
+                if let Some((_, content)) = self.raw_match_edit.as_mut() {
+                    content.perform(action);
+                }
+            }
+            Message::ApplyRawMatchPressed => {
+                let Some((j, content)) = self.raw_match_edit.as_ref() else {
+                    return Task::none();
+                };
+                match serde_yaml::from_str::<serde_yaml::Value>(&content.text()) {
+                    Ok(value) if value.is_mapping() => {
+                        let preserved_matches = &mut self.edited_file.preserved_matches;
+                        if let Some((_, preserved)) = preserved_matches.get_mut(*j) {
+                            *preserved = value;
+                        }
+                        self.raw_match_edit = None;
+                    }
+                    Ok(_) => self.open_modal(
+                        "Invalid Match".to_string(),
+                        "A match should be a set of keys, like trigger: and form:.".to_string(),
+                        "OK",
+                        None,
+                    ),
+                    Err(err) => self.open_modal(
+                        "Invalid YAML".to_string(),
+                        format!("The match isn't valid YAML: {}", err),
+                        "OK",
+                        None,
+                    ),
+                }
+            }
+            Message::CancelRawMatchPressed => self.raw_match_edit = None,
+            Message::EditGlobalVars(action) => match action {
+                text_editor::Action::Scroll { lines: _ } => {}
+                action => {
+                    let is_edit = action.is_edit();
+                    self.global_vars_te.perform(action);
+
+                    if is_edit {
+                        let yaml = self.global_vars_te.text();
+                        let parsed = if yaml.trim().is_empty() {
+                            Ok(Vec::new())
+                        } else {
+                            serde_yaml::from_str::<Vec<serde_yaml::Value>>(&yaml)
+                        };
+                        match parsed {
+                            Ok(global_vars) => {
+                                self.edited_file.global_vars = global_vars;
+                                self.global_vars_error = None;
+                            }
+                            Err(err) => self.global_vars_error = Some(err.to_string()),
+                        }
+                    }
+                }
+            },
+            Message::ExpandReplacePressed(index) => {
+                self.multiline_replace.insert(index);
+                self.expanded_replace = Some(index);
+            }
+            Message::CloseExpandedReplace => self.expanded_replace = None,
+            Message::TestMatchPressed(index) => {
+                if let Some(a_match) = self.edited_file.matches.get(index) {
+                    let expansion = render_replace(&a_match.replace, &self.edited_file.global_vars);
+                    let output = self
+                        .test_output
+                        .get_or_insert_with(text_editor::Content::new);
+                    // Insert where the cursor is, like espanso typing into a field
+                    output.perform(text_editor::Action::Edit(text_editor::Edit::Paste(
+                        std::sync::Arc::new(expansion),
+                    )));
+                }
+            }
+            Message::TestOutputAction(action) => {
+                if let Some(output) = self.test_output.as_mut() {
+                    output.perform(action);
+                }
+            }
+            Message::CloseTestOutput => self.test_output = None,
+            Message::PickImagePressed(index) => {
+                let selected_image = FileDialog::new()
+                    .add_filter("Image", &["png", "jpg", "jpeg", "gif", "bmp", "webp"])
+                    .set_directory(self.selected_file.parent().unwrap_or(Path::new("")))
+                    .pick_file();
+                if let (Some(image), Some(a_match)) =
+                    (selected_image, self.edited_file.matches.get_mut(index))
+                {
+                    // Keep images inside the espanso dir portable with $CONFIG
+                    let image_path = match image.strip_prefix(&self.espanso_loc) {
+                        Ok(relative) => format!("$CONFIG/{}", slash_path(relative)),
+                        Err(_) => image.display().to_string(),
+                    };
+                    a_match.image_path = Some(image_path);
+                }
+            }
+            Message::SearchTermsToggled(index) => {
+                if self.search_terms_input.remove(&index).is_none() {
+                    if let Some(a_match) = self.edited_file.matches.get(index) {
+                        self.search_terms_input
+                            .insert(index, a_match.search_terms.join(", "));
+                    }
+                }
+            }
+            Message::SearchTermsInput(index, value) => {
+                if let Some(a_match) = self.edited_file.matches.get_mut(index) {
+                    a_match.search_terms = value
+                        .split(',')
+                        .map(|term| term.trim().to_string())
+                        .filter(|term| !term.is_empty())
+                        .collect();
+                    self.search_terms_input.insert(index, value);
+                }
+            }
+            Message::WordToggled(index, word) => {
+                if let Some(a_match) = self.edited_file.matches.get_mut(index) {
+                    a_match.word = word;
+                }
+            }
+            Message::SwapTriggerReplacePressed(index) => {
+                if let Some(a_match) = self.edited_file.matches.get_mut(index) {
+                    std::mem::swap(&mut a_match.trigger, &mut a_match.replace);
+                    self.edited_file_te[index] = text_editor::Content::with_text(&a_match.replace);
+                }
+            }
+            Message::TrimTriggerPressed(index) => {
+                if let Some(a_match) = self.edited_file.matches.get_mut(index) {
+                    a_match.trigger = a_match.trigger.trim().to_string();
+                }
+            }
+            Message::TrimAllTriggersPressed => {
+                for a_match in &mut self.edited_file.matches {
+                    if has_untrimmed_trigger(a_match) {
+                        a_match.trigger = a_match.trigger.trim().to_string();
+                    }
+                }
+            }
+            Message::NoteInput(index, note) => {
+                if let Some(a_match) = self.edited_file.matches.get_mut(index) {
+                    a_match.note = note;
+                }
+            }
+            Message::RevertMatchPressed(index) => {
+                if let Some(original) = self.original_file.matches.get(index) {
+                    if index < self.edited_file.matches.len() {
+                        self.edited_file.matches[index] = original.clone();
+                        self.search_terms_input.remove(&index);
+                        self.edited_file_te[index] =
+                            text_editor::Content::with_text(&original.replace);
+                    }
+                }
+            }
+            Message::PreviewYamlPressed => {
+                // Same serializer the save path uses, just into a string
+                let yaml = if self.selected_nav == "eg-Config" {
+                    // Previewing would quietly show the old separators
+                    if let Err(err) = parse_word_separators(&self.temp_word_separators) {
+                        self.show_word_separators_error(err);
+                        return Task::none();
+                    }
+                    config_yaml(&self.config_to_write())
+                } else {
+                    self.edited_file.to_yaml_with(self.block_scalar_replace)
+                };
+                match yaml {
+                    Ok(yaml) => self.yaml_preview = Some(text_editor::Content::with_text(&yaml)),
+                    Err(err) => eprintln!("Couldn't serialize preview: {}", err),
+                }
+            }
+            Message::YamlPreviewAction(action) => {
+                // Read-only: allow selecting and scrolling but not editing
+                if let Some(preview) = self.yaml_preview.as_mut() {
+                    if !action.is_edit() {
+                        preview.perform(action);
+                    }
+                }
+            }
+            Message::YamlPreviewSavePressed => {
+                self.yaml_preview = None;
+                return Task::done(if self.selected_nav == "eg-Config" {
+                    Message::SaveConfigPressed
+                } else {
+                    Message::SaveFilePressed
+                });
+            }
+            Message::CloseYamlPreview => self.yaml_preview = None,
+            Message::CompareConfigPressed => {
+                let Some(other_file) = FileDialog::new()
+                    .set_directory(Path::new(&self.espanso_loc).join("config"))
+                    .add_filter("YAML", &["yml", "yaml"])
+                    .pick_file()
+                else {
+                    return Task::none();
+                };
+                match ParsedConfig::load(&other_file) {
+                    Ok(other) => {
+                        self.config_comparison = Some((
+                            other_file
+                                .file_name()
+                                .unwrap_or_default()
+                                .to_string_lossy()
+                                .to_string(),
+                            config_differences(&self.edited_config, &other),
+                        ))
+                    }
+                    Err(err) => self.open_modal(
+                        "Couldn't Compare".to_string(),
+                        format!("{} couldn't be read as a config: {}", other_file.display(), err),
+                        "OK",
+                        None,
+                    ),
+                }
+            }
+            Message::CloseConfigComparison => self.config_comparison = None,
+            Message::ValidateMatchFilesPressed => {
+                if self.validation_progress.is_none() {
+                    self.validation_progress = Some(0);
+                    let match_dir = PathBuf::from(&self.espanso_loc).join("match");
+                    return Task::run(
+                        validate_match_files(
+                            match_dir,
+                            self.max_file_size_mb,
+                            self.preserve_empty_matches,
+                        ),
+                        |message| message,
+                    );
+                }
+            }
+            Message::ValidationProgress(checked) => {
+                if self.validation_progress.is_some() {
+                    self.validation_progress = Some(checked);
+                }
+            }
+            Message::ValidationFinished(match_dir, report) => {
+                self.validation_progress = None;
+                // Drop a report for a directory that's no longer selected
+                if match_dir == PathBuf::from(&self.espanso_loc).join("match") {
+                    self.validation_report = Some(report);
+                }
+            }
+            Message::CloseValidationReport => self.validation_report = None,
+            Message::QuickAddTriggerInput(value) => {
+                let i = self.edited_file.matches.len();
+                // Anything already typed in the replace box comes along
+                let content = std::mem::take(&mut self.quick_add_te);
+                self.edited_file.matches.push(YamlPairs {
+                    trigger: value,
+                    replace: content.text().trim_end_matches('\n').to_string(),
+                    ..Default::default()
+                });
+                self.edited_file_te.push(content);
+                // The committed row takes this row's place, so keep typing in it
+                return Task::batch([
+                    text_input::focus(trigger_input_id(i)),
+                    text_input::move_cursor_to_end(trigger_input_id(i)),
+                ]);
+            }
+            // Only the trigger commits the row. text_editor can't be focused
+            // from a task, so committing here would drop focus after one key.
+            Message::QuickAddReplace(action) => match action {
+                text_editor::Action::Scroll { lines: _ } => {}
+                action => self.quick_add_te.perform(action),
+            },
+            Message::NavMoved(step) => {
+                if self.show_modal {
+                    return Task::none();
+                }
+                let destinations = self.nav_destinations();
+                let current = self.nav_highlight.as_ref().unwrap_or(&self.selected_nav);
+                let next = match destinations.iter().position(|dest| dest == current) {
+                    Some(index) => index
+                        .saturating_add_signed(step)
+                        .min(destinations.len().saturating_sub(1)),
+                    None => 0,
+                };
+                self.nav_highlight = destinations.get(next).cloned();
+            }
+            Message::NavOpenPressed => {
+                if self.show_modal {
+                    return Task::none();
+                }
+                if let Some(destination) = self.nav_highlight.take() {
+                    if destination != "eg-About" && self.unsaved_changes() {
+                        self.confirm_leave_file(PendingAction::Navigate(destination));
+                    } else {
+                        return self.update(Message::NavigateTo(destination));
+                    }
+                }
+            }
+            Message::NavEscapePressed => self.nav_highlight = None,
+            Message::SaveShortcutPressed => match self.selected_nav.as_str() {
+                "eg-Config" => return self.update(Message::SaveConfigPressed),
+                "" | "eg-Settings" => {}
+                _ => return self.update(Message::SaveFilePressed),
+            },
+            Message::ShortcutHelpToggled => self.show_shortcuts = !self.show_shortcuts,
+            Message::ReloadAllPressed => {
+                if self.unsaved_changes() {
+                    self.open_modal(
+                        "Unsaved Changes".to_string(),
+                        "Reloading from disk will erase any unsaved changes.".to_string(),
+                        "Reload",
+                        Some(PendingAction::ReloadAll),
+                    );
+                } else {
+                    return self.perform_action(PendingAction::ReloadAll);
+                }
+            }
+            Message::CycleFilePressed(step) => {
+                if self.match_files.is_empty() {
+                    return Task::none();
+                }
+                // Wraps around; from a screen that isn't a file, start at either end
+                let count = self.match_files.len() as isize;
+                let next = match self
+                    .match_files
+                    .iter()
+                    .position(|stem| *stem == self.selected_nav)
+                {
+                    Some(current) => (current as isize + step).rem_euclid(count),
+                    None if step > 0 => 0,
+                    None => count - 1,
+                };
+                let destination = self.match_files[next as usize].clone();
+                if destination == self.selected_nav {
+                    return Task::none();
+                }
+                if self.unsaved_changes() {
+                    self.confirm_leave_file(PendingAction::Navigate(destination));
+                } else {
+                    return self.update(Message::NavigateTo(destination));
+                }
+            }
+            Message::RefreshLogsPressed => {
+                if !self.logs_loading {
+                    self.logs_loading = true;
+                    return Task::perform(async { read_espanso_log() }, Message::LogsLoaded);
+                }
+            }
+            Message::LogsLoaded(logs) => {
+                self.logs_loading = false;
+                self.logs = text_editor::Content::with_text(&logs);
+                // Newest lines are at the bottom
+                self.logs
+                    .perform(text_editor::Action::Move(text_editor::Motion::DocumentEnd));
+            }
+            Message::LogsAction(action) => {
+                // Read-only: allow selecting and scrolling but not editing
+                if !action.is_edit() {
+                    self.logs.perform(action);
+                }
+            }
+            Message::AutoSaveToggled(value) => {
+                self.auto_save = value;
+                self.persist_egui_data();
+            }
+            Message::AutoSaveDelayInput(value) => {
+                self.auto_save_delay = value;
+                self.persist_egui_data();
+            }
+            Message::AutoSaveTick => {
+                // Any interaction restarts the wait, so a trigger that's still
+                // being typed isn't written half finished
+                let waited = self.last_activity.is_some_and(|last_activity| {
+                    last_activity.elapsed() >= Duration::from_secs(self.auto_save_delay)
+                });
+                if !waited
+                    || self.show_modal
+                    || self.auto_save_status.is_some()
+                    || self.edited_file == self.original_file
+                {
+                    return Task::none();
+                }
+                let blocker = if self.has_empty_fields() && !self.preserve_empty_matches {
+                    Some("a match has an empty trigger or replace")
+                } else if self.global_vars_error.is_some() {
+                    Some("the global variables aren't valid YAML")
+                } else if (0..self.edited_file.matches.len())
+                    .any(|i| self.is_duplicate_trigger(i))
+                {
+                    Some("two matches use the same trigger")
+                } else if self.first_changed_by_round_trip().is_some() {
+                    Some("a match wouldn't save correctly")
+                } else if self.modified_since_load() {
+                    Some("the file was changed outside espansoGUI")
+                } else {
+                    None
+                };
+                self.auto_save_status = Some(match blocker {
+                    Some(blocker) => format!("Not saved automatically because {}.", blocker),
+                    None if self.save_file() => "Saved automatically.".to_string(),
+                    None => "Automatic save failed.".to_string(),
+                });
+            }
+            Message::CopyLogsPressed => {
+                self.toast = Some("Copied logs".to_string());
+                return iced::clipboard::write(self.logs.text());
+            }
+            Message::CopyDebugInfoPressed => {
+                let debug_info = self.debug_info();
+                self.toast = Some("Copied debug info".to_string());
+                return iced::clipboard::write(debug_info);
+            }
+            Message::ScanProgress(found) => {
+                if self.scan_progress.is_some() {
+                    self.scan_progress = Some(found);
+                }
+            }
+            Message::ScanFinished(match_dir, match_files, disabled_match_files) => {
+                self.scan_progress = None;
+                if self.rescan_pending {
+                    self.rescan_pending = false;
+                    return self.refresh_match_files();
+                }
+                // Drop results for a directory that's no longer selected
+                if match_dir == PathBuf::from(self.espanso_loc.clone()).join("match") {
+                    self.match_files = match_files;
+                    self.disabled_match_files = disabled_match_files;
+                    if self.trigger_search_results.is_some() {
+                        self.run_trigger_search();
+                    }
+                    if !self.show_modal && self.offer_to_recreate_match_dir() {
+                        return Task::none();
+                    }
+                    if let Some(destination) = self.pending_open.take() {
+                        if destination == "eg-Config"
+                            || self.match_files.contains(&destination)
+                            || self.disabled_match_files.contains(&destination)
+                        {
+                            return self.update(Message::NavigateTo(destination));
+                        }
+                    }
+                }
+            }
+            Message::MatchCountsFound(match_dir, match_counts, unopenable_files) => {
+                if match_dir == PathBuf::from(self.espanso_loc.clone()).join("match") {
+                    self.match_counts = match_counts;
+                    self.unopenable_files = unopenable_files;
+                }
+            }
             Message::TabPressed { shift } => {
                 if shift {
                     return widget::focus_previous();
@@ -597,15 +2580,793 @@ impl EGUI {
         Task::none()
     }
 
-    pub fn view(&self) -> Element<Message> {
-        let unsaved_changes = self.edited_file.matches != self.original_file.matches;
-        let word_separators_changed = self.temp_word_separators.to_owned()
-            != if self.edited_config.word_separators.is_some() {
-                serde_json::to_string(&self.edited_config.word_separators.clone().unwrap())
-                    .unwrap_or_default()
-            } else {
-                format!("{:?}", get_default_word_separators())
+    fn activate_espanso_dir(&mut self) -> Task<Message> {
+        if valid_espanso_dir(self.espanso_loc.clone()) {
+            self.directory_invalid = false;
+            self.onboarding = false;
+            if self.selected_nav == "eg-DirError" {
+                self.selected_nav = "eg-Settings".to_string();
+            }
+            add_recent_dir(&mut self.recent_dirs, &self.espanso_loc);
+            self.persist_egui_data();
+            self.match_files.clear();
+            self.disabled_match_files.clear();
+            self.refresh_match_files()
+        } else {
+            self.directory_invalid = true;
+            let espanso_dir = PathBuf::from(self.espanso_loc.clone());
+            if !self.offer_to_create_default_config(&espanso_dir) {
+                self.suggest_espanso_dir(&espanso_dir);
+            }
+            Task::none()
+        }
+    }
+
+    // A directory with config/ and match/ but no config/default.yml most likely
+    // had it deleted by accident, so offer to write a fresh one. Returns
+    // whether that was the case.
+    fn offer_to_create_default_config(&mut self, espanso_dir: &Path) -> bool {
+        if !espanso_dir.join("config").is_dir()
+            || !espanso_dir.join("match").is_dir()
+            || espanso_dir.join("config/default.yml").exists()
+        {
+            return false;
+        }
+        self.open_modal(
+            "Default Config Missing".to_string(),
+            format!(
+                "{} has no config/default.yml, which espanso needs. Create one with the default options?",
+                espanso_dir.display()
+            ),
+            "Create",
+            Some(PendingAction::CreateDefaultConfig(
+                espanso_dir.display().to_string(),
+            )),
+        );
+        true
+    }
+
+    // match/ can be deleted after the directory was chosen, which would otherwise
+    // only show up as an empty file list and failing file creation. Returns
+    // whether it was missing.
+    fn offer_to_recreate_match_dir(&mut self) -> bool {
+        let match_dir = PathBuf::from(&self.espanso_loc).join("match");
+        if self.espanso_loc.is_empty() || match_dir.is_dir() {
+            return false;
+        }
+        self.open_modal(
+            "Match Folder Missing".to_string(),
+            format!(
+                "{} doesn't exist, so there are no match files to show and new ones can't be created. Create it again?",
+                match_dir.display()
+            ),
+            "Create",
+            Some(PendingAction::RecreateMatchDir),
+        );
+        true
+    }
+
+    // Users often pick config/ or match/ (or a folder inside match/) instead of
+    // the espanso directory itself, so offer the enclosing espanso directory
+    fn suggest_espanso_dir(&mut self, selected_dir: &Path) {
+        let Some(espanso_dir) = selected_dir
+            .ancestors()
+            .skip(1)
+            .take(3)
+            .find(|dir| valid_espanso_dir(dir.display().to_string()))
+        else {
+            return;
+        };
+        self.open_modal(
+            "Use the espanso folder?".to_string(),
+            format!(
+                "{} is inside your espanso folder rather than the folder itself. Use {} instead?",
+                selected_dir.display(),
+                espanso_dir.display()
+            ),
+            "Use",
+            Some(PendingAction::UseEspansoDir(
+                espanso_dir.display().to_string(),
+            )),
+        );
+    }
+
+    // Rescans match/ in the background. Requests made while a scan is running
+    // are folded into a single rescan once it finishes.
+    fn refresh_match_files(&mut self) -> Task<Message> {
+        if self.scan_progress.is_some() {
+            self.rescan_pending = true;
+            return Task::none();
+        }
+        self.scan_progress = Some(0);
+        let match_dir = PathBuf::from(self.espanso_loc.clone()).join("match");
+        Task::run(
+            scan_match_files(match_dir, self.max_file_size_mb),
+            |message| message,
+        )
+    }
+
+    fn run_trigger_search(&mut self) {
+        let query = self.trigger_search.trim();
+        if query.is_empty() {
+            self.trigger_search_results = None;
+            return;
+        }
+        self.trigger_search_results =
+            Some(search_triggers(&self.espanso_loc, &self.match_files, query));
+    }
+
+    fn match_file_path(&self, stem: &str) -> PathBuf {
+        if Path::new(stem).is_absolute() {
+            return PathBuf::from(stem);
+        }
+        match_file_path(
+            &self.espanso_loc,
+            stem,
+            !self.match_files.iter().any(|file| file == stem)
+                && self.disabled_match_files.iter().any(|file| file == stem),
+        )
+    }
+
+    fn persist_egui_data(&self) {
+        let new_egui_data = EGUIData {
+            espanso_dir: self.espanso_loc.clone(),
+            recent_dirs: self.recent_dirs.clone(),
+            software_renderer: self.software_renderer,
+            present_mode: self.present_mode.clone(),
+            antialiasing: self.antialiasing,
+            high_contrast: self.high_contrast,
+            browser_command: self.browser_command.clone(),
+            accent_color: self.accent_color.clone(),
+            large_file_threshold: self.large_file_threshold,
+            max_file_size_mb: self.max_file_size_mb,
+            auto_save: self.auto_save,
+            auto_save_delay: self.auto_save_delay,
+            preserve_empty_matches: self.preserve_empty_matches,
+            compact_view: self.compact_view,
+            file_compact_views: self.file_compact_views.clone(),
+            always_multiline_replace: self.always_multiline_replace,
+            warn_cursor_hints: self.warn_cursor_hints,
+            block_scalar_replace: self.block_scalar_replace,
+            config_presets: self.config_presets.clone(),
+        };
+        let _ = write_egui_data(&new_egui_data);
+    }
+
+    // Environment details to paste into a bug report
+    fn debug_info(&self) -> String {
+        let mut info = vec![
+            format!("espansoGUI: {}", env!("CARGO_PKG_VERSION")),
+            format!("OS: {} {}", env::consts::OS, env::consts::ARCH),
+            format!("espanso: {}", espanso_version()),
+            format!("espanso directory: {}", self.espanso_loc),
+            format!(
+                "Renderer: {}",
+                if self.software_renderer {
+                    "software"
+                } else {
+                    "default"
+                }
+            ),
+            format!("Settings: {}", get_app_config_dir().display()),
+        ];
+        if !self.recent_toasts.is_empty() {
+            info.push("Recent messages:".to_string());
+            info.extend(
+                self.recent_toasts
+                    .iter()
+                    .map(|toast| format!("- {}", toast)),
+            );
+        }
+        info.join("\n")
+    }
+
+    fn has_empty_fields(&self) -> bool {
+        self.edited_file.matches.iter().any(|pairs| {
+            let replace_empty = match &pairs.image_path {
+                Some(image_path) => image_path.trim().is_empty(),
+                None => pairs.replace.trim().is_empty(),
             };
+            pairs.trigger.trim().is_empty() || replace_empty
+        })
+    }
+
+    fn count_triggers(&mut self) {
+        self.trigger_counts.clear();
+        let preserved_triggers = self
+            .edited_file
+            .preserved_matches
+            .iter()
+            .filter_map(|(_, value)| value.get("trigger")?.as_str());
+        for trigger in self
+            .edited_file
+            .matches
+            .iter()
+            .map(|a_match| a_match.trigger.as_str())
+            .chain(preserved_triggers)
+        {
+            *self.trigger_counts.entry(trigger.to_string()).or_default() += 1;
+        }
+    }
+
+    fn is_duplicate_trigger(&self, i: usize) -> bool {
+        let trigger = &self.edited_file.matches[i].trigger;
+        !trigger.is_empty()
+            && self
+                .trigger_counts
+                .get(trigger)
+                .is_some_and(|count| *count > 1)
+    }
+
+    fn is_compact_view(&self) -> bool {
+        self.file_compact_views
+            .get(&self.selected_nav)
+            .copied()
+            .unwrap_or(self.compact_view)
+    }
+
+    fn single_line_replace(&self, i: usize) -> bool {
+        !self.always_multiline_replace
+            && !self.multiline_replace.contains(&i)
+            && !self.edited_file.matches[i].replace.contains('\n')
+    }
+
+    fn selected_pairs(&self) -> Vec<YamlPairs> {
+        self.selected_matches
+            .iter()
+            .filter_map(|i| self.edited_file.matches.get(*i).cloned())
+            .collect()
+    }
+
+    // Appends the selected matches to another file and removes them from
+    // this one, asking first if rewriting the destination would lose anything
+    fn move_selected_to(&mut self, destination: String, allow_lossy: bool) {
+        if self.edited_file != self.original_file {
+            self.open_modal(
+                "Unsaved Changes".to_string(),
+                "Save or reset your changes before moving matches to another file.".to_string(),
+                "OK",
+                None,
+            );
+            return;
+        }
+        let destination_path = self.match_file_path(&destination);
+        let read = check_match_file(&destination_path, self.max_file_size_mb)
+            .and_then(|_| open_match_file(&destination_path, self.preserve_empty_matches));
+        let mut destination_file = match read {
+            Ok((_, _, Some(reason))) if !allow_lossy => {
+                self.open_modal(
+                    "Move matches anyway?".to_string(),
+                    format!(
+                        "{} {} Moving matches into it rewrites the whole file.",
+                        destination, reason
+                    ),
+                    "Move Anyway",
+                    Some(PendingAction::MoveSelectedAnyway(destination)),
+                );
+                return;
+            }
+            Ok((file, _, _)) => file,
+            Err(reason) => {
+                self.open_modal(
+                    "Couldn't move matches".to_string(),
+                    format!("{} {}", destination, reason),
+                    "OK",
+                    None,
+                );
+                return;
+            }
+        };
+        destination_file.matches.extend(self.selected_pairs());
+        if let Err(err) = write_from_triggers(
+            destination_path,
+            destination_file,
+            self.block_scalar_replace,
+        ) {
+            self.open_modal(
+                "Couldn't move matches".to_string(),
+                format!("{} couldn't be written: {}", destination, err),
+                "OK",
+                None,
+            );
+            return;
+        }
+
+        let moved = self.selected_matches.len();
+        self.remove_selected_matches();
+        if self.save_file() {
+            self.toast = Some(format!("Moved {} match(es) to {}", moved, destination));
+        }
+    }
+
+    fn remove_selected_matches(&mut self) {
+        // Remove from the end so earlier indices stay valid
+        for i in self.selected_matches.iter().rev() {
+            if *i < self.edited_file.matches.len() {
+                self.edited_file.remove_match(*i);
+                self.edited_file_te.remove(*i);
+            }
+        }
+        self.selected_matches.clear();
+        self.search_terms_input.clear();
+        self.multiline_replace.clear();
+        self.compact_expanded = None;
+        self.round_trip_error = None;
+    }
+
+    // Rebuilds the editor contents from edited_file after it was replaced wholesale
+    fn sync_editors(&mut self) {
+        self.edited_file_te = self
+            .edited_file
+            .matches
+            .iter()
+            .map(|a_match| text_editor::Content::with_text(&a_match.replace))
+            .collect();
+        self.global_vars_te = if self.edited_file.global_vars.is_empty() {
+            text_editor::Content::new()
+        } else {
+            text_editor::Content::with_text(
+                &serde_yaml::to_string(&self.edited_file.global_vars).unwrap_or_default(),
+            )
+        };
+        self.global_vars_error = None;
+    }
+
+    // Entries of the nav column in display order
+    fn nav_destinations(&self) -> Vec<String> {
+        self.match_files
+            .iter()
+            .chain(self.disabled_match_files.iter())
+            .cloned()
+            .chain(["eg-Config", "eg-Settings", "eg-Logs", "eg-About"].map(String::from))
+            .collect()
+    }
+
+    // Returns whether the file was written; failures are shown to the user and
+    // leave original_file alone so the changes still show as unsaved
+    fn save_file(&mut self) -> bool {
+        if !self.selected_file_under("match") && !self.is_external_file() {
+            eprintln!("Not saving: no match file is open");
+            return false;
+        }
+        match write_from_triggers(
+            self.selected_file.clone(),
+            self.edited_file.clone(),
+            self.block_scalar_replace,
+        ) {
+            Ok(_) => {
+                self.original_file = self.edited_file.clone();
+                self.selected_file_modified = get_modified_time(&self.selected_file);
+                self.round_trip_error = None;
+                self.lossy_file = None;
+                if self.match_counts.contains_key(&self.selected_nav) {
+                    self.match_counts
+                        .insert(self.selected_nav.clone(), match_count(&self.edited_file));
+                }
+                true
+            }
+            Err(err) => {
+                eprintln!("Failed to save file: {}", err);
+                self.open_modal(
+                    "Couldn't save file".to_string(),
+                    format!(
+                        "{} couldn't be written: {}",
+                        self.selected_file.display(),
+                        err
+                    ),
+                    "OK",
+                    None,
+                );
+                false
+            }
+        }
+    }
+
+    // Writes the file to YAML and reads it back the way it will be loaded,
+    // returning the first match that doesn't survive unchanged
+    fn first_changed_by_round_trip(&self) -> Option<usize> {
+        first_changed_by_round_trip(
+            &self.edited_file,
+            self.block_scalar_replace,
+            self.preserve_empty_matches,
+        )
+    }
+
+    fn show_word_separators_error(&mut self, err: String) {
+        self.open_modal(
+            "Invalid Word Separators".to_string(),
+            format!(
+                "Word separators should be a list of quoted characters, like [\" \", \",\", \"\\t\", \"\\n\"]. {} Your change hasn't been applied.",
+                err
+            ),
+            "OK",
+            None,
+        );
+    }
+
+    fn word_separators_changed(&self) -> bool {
+        let current = self
+            .edited_config
+            .word_separators
+            .clone()
+            .unwrap_or_else(get_default_word_separators);
+        parse_word_separators(&self.temp_word_separators).ok() != Some(current)
+    }
+
+    // Leaving the current screen would lose edits to the open file or, on the
+    // Config screen, to the config
+    fn unsaved_changes(&self) -> bool {
+        self.edited_file != self.original_file
+            || (self.selected_nav == "eg-Config" && self.config_changed())
+    }
+
+    // Compares the configs as espanso reads them, so an unset backend or
+    // toggle key matches its default and "auto" matches "Auto"
+    fn config_changed(&self) -> bool {
+        normalized_config(&self.original_config) != normalized_config(&self.edited_config)
+            || self.word_separators_changed()
+    }
+
+    // The config as it would be written, with the word separators text parsed back in
+    fn config_to_write(&self) -> ParsedConfig {
+        let mut config = self.edited_config.clone();
+        // Write pick list options in the casing espanso's docs use
+        config.backend = canonical_option(&BACKEND_OPTIONS, &config.backend);
+        config.toggle_key = canonical_option(&TOGGLE_KEY_OPTIONS, &config.toggle_key);
+        if self.word_separators_changed() {
+            match parse_word_separators(&self.temp_word_separators) {
+                Ok(value) => config.word_separators = Some(value),
+                Err(err) => eprintln!("Couldn't parse word separators: {}", err),
+            }
+        }
+        omit_untouched_defaults(&mut config, &self.original_config, &self.touched_config);
+
+        config
+    }
+
+    fn save_config(&mut self) {
+        if !self.selected_file_under("config") {
+            eprintln!("Not saving: no config file is open");
+            return;
+        }
+        self.edited_config = self.config_to_write();
+        if let Err(err) = overwrite_config(&self.selected_file, &self.edited_config) {
+            eprintln!("Failed to save config: {}", err);
+            self.open_modal(
+                "Couldn't save config".to_string(),
+                format!("{} couldn't be written: {}", self.selected_file_name(), err),
+                "OK",
+                None,
+            );
+            return;
+        }
+        self.original_config = self.edited_config.clone();
+        self.touched_config.clear();
+        self.temp_word_separators = word_separators_text(&self.edited_config);
+        self.selected_file_modified = get_modified_time(&self.selected_file);
+        // espanso only picks up config changes by itself when auto_restart is on
+        self.toast = Some(if self.edited_config.auto_restart == Some(false) {
+            format!(
+                "Saved {}. Restart espanso to apply it.",
+                self.selected_file_name()
+            )
+        } else {
+            format!("Saved {}", self.selected_file_name())
+        });
+    }
+
+    // Saves only write to a .yml file inside the espanso folder's match or
+    // config folder, never to the empty path the other screens leave behind
+    fn selected_file_under(&self, folder: &str) -> bool {
+        self.selected_file
+            .extension()
+            .is_some_and(|ext| ext == "yml")
+            && self
+                .selected_file
+                .starts_with(Path::new(&self.espanso_loc).join(folder))
+    }
+
+    // A file opened from outside match/, which the nav tracks by its full path
+    fn is_external_file(&self) -> bool {
+        Path::new(&self.selected_nav).is_absolute()
+    }
+
+    fn selected_file_name(&self) -> String {
+        self.selected_file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    // Appends pasted matches, skipping triggers the file already has.
+    // Returns how many were added and skipped.
+    fn append_pasted_pairs(&mut self, pairs: Vec<(String, String)>) -> (usize, usize) {
+        let pasted = EspansoYaml {
+            matches: pairs
+                .into_iter()
+                .filter(|(trigger, _)| !trigger.is_empty())
+                .map(|(trigger, replace)| YamlPairs {
+                    trigger,
+                    replace,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let previous_len = self.edited_file.matches.len();
+        let counts = self.edited_file.append(pasted);
+        for pair in &self.edited_file.matches[previous_len..] {
+            self.edited_file_te
+                .push(text_editor::Content::with_text(&pair.replace));
+        }
+        counts
+    }
+
+    fn record_config_step(&mut self, field: &'static str, before: ParsedConfig) {
+        if self.config_history_field != Some(field) {
+            self.config_history.push((field, before));
+            let excess = self.config_history.len().saturating_sub(MAX_CONFIG_HISTORY);
+            self.config_history.drain(..excess);
+        }
+        self.config_history_field = Some(field);
+    }
+
+    fn modified_since_load(&self) -> bool {
+        let modified = get_modified_time(&self.selected_file);
+        modified.is_some() && modified != self.selected_file_modified
+    }
+
+    fn show_modified_modal(&mut self, overwrite_action: PendingAction) {
+        self.open_modal(
+            "File changed on disk".to_string(),
+            format!(
+                "{} was modified outside of espansoGUI since it was opened. Overwrite it with your changes, or reload it from disk and lose them?",
+                self.selected_file_name()
+            ),
+            "Overwrite",
+            Some(overwrite_action),
+        );
+        self.modal_alt_action = Some(("Reload".to_string(), PendingAction::Reload));
+    }
+
+    // The last step of saving a match file, once its matches have been checked
+    fn save_file_unless_modified(&mut self) {
+        if self.modified_since_load() {
+            self.show_modified_modal(PendingAction::OverwriteFile);
+        } else if self.save_file() {
+            self.toast = Some(format!("Saved {}", self.selected_file_name()));
+        }
+    }
+
+    // Keep the first $|$ in one match's replace, or every match's when None
+    fn fix_cursor_hints(&mut self, index: Option<usize>) {
+        for (i, a_match) in self.edited_file.matches.iter_mut().enumerate() {
+            if index.is_some_and(|index| index != i) || extra_cursor_hints(&a_match.replace) == 0 {
+                continue;
+            }
+            a_match.replace = keep_first_cursor_hint(&a_match.replace);
+            if let Some(content) = self.edited_file_te.get_mut(i) {
+                *content = text_editor::Content::with_text(&a_match.replace);
+            }
+        }
+    }
+
+    fn open_modal(
+        &mut self,
+        title: String,
+        description: String,
+        ok_text: &str,
+        action: Option<PendingAction>,
+    ) {
+        self.modal_title = title;
+        self.modal_description = description;
+        self.modal_ok_text = ok_text.to_string();
+        self.modal_action = action;
+        self.modal_alt_action = None;
+        self.show_modal = true;
+    }
+
+    fn confirm_leave_file(&mut self, action: PendingAction) {
+        self.open_modal(
+            "Unsaved Changes".to_string(),
+            "Leaving this file will erase any unsaved changes.".to_string(),
+            "OK",
+            Some(action),
+        );
+    }
+
+    fn close_modal(&mut self) {
+        self.show_modal = false;
+        self.modal_ok_text = "OK".to_string();
+        self.modal_action = None;
+        self.modal_alt_action = None;
+    }
+
+    fn perform_action(&mut self, action: PendingAction) -> Task<Message> {
+        match action {
+            PendingAction::Navigate(destination) => {
+                return self.update(Message::NavigateTo(destination));
+            }
+            PendingAction::DeleteFile => {
+                // Read first so the deletion can be undone from the toast
+                let contents = std::fs::read(&self.selected_file);
+                match remove_file(self.selected_file.clone()) {
+                    Ok(_) => {
+                        let toast = format!("Deleted {}", self.selected_nav);
+                        self.deleted_file = contents.ok().map(|contents| {
+                            (
+                                self.selected_nav.clone(),
+                                self.disabled_match_files.contains(&self.selected_nav),
+                                contents,
+                            )
+                        });
+                        self.toast = Some(toast);
+                    }
+                    Err(err) => eprintln!("Failed to delete file: {}", err),
+                }
+                if self.file_compact_views.remove(&self.selected_nav).is_some() {
+                    self.persist_egui_data();
+                }
+                // Update file list
+                self.match_files.retain(|file| *file != self.selected_nav);
+                self.disabled_match_files
+                    .retain(|file| *file != self.selected_nav);
+                let scan = self.refresh_match_files();
+                // Navigate back to Settings
+                return Task::batch([
+                    scan,
+                    self.update(Message::NavigateTo("eg-Settings".to_string())),
+                ]);
+            }
+            PendingAction::DuplicateFile => {
+                let disabled = self.disabled_match_files.contains(&self.selected_nav);
+                let base_stem = format!("{}-copy", self.selected_nav);
+                let mut copy_stem = base_stem.clone();
+                let mut n = 2;
+                while self.match_files.contains(&copy_stem)
+                    || self.disabled_match_files.contains(&copy_stem)
+                    || match_file_path(&self.espanso_loc, &copy_stem, disabled).exists()
+                {
+                    copy_stem = format!("{}-{}", base_stem, n);
+                    n += 1;
+                }
+                // Copy the bytes so comments and formatting come along
+                let copy_path = match_file_path(&self.espanso_loc, &copy_stem, disabled);
+                match copy(&self.selected_file, copy_path) {
+                    Ok(_) => {
+                        // Known right away; the rescan just confirms it
+                        if disabled {
+                            self.disabled_match_files.push(copy_stem.clone());
+                        } else {
+                            self.match_files.push(copy_stem.clone());
+                        }
+                        let scan = self.refresh_match_files();
+                        return Task::batch([scan, self.update(Message::NavigateTo(copy_stem))]);
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to duplicate file: {}", err);
+                        self.toast = Some(format!("Couldn't duplicate file: {}", err));
+                    }
+                }
+            }
+            PendingAction::FormatFile => {
+                let parsed = read_yaml_text(&self.selected_file)
+                    .map_err(|err| err.to_string())
+                    .and_then(|(yaml, _)| {
+                        EspansoYaml::parse(&yaml, false).map_err(|err| err.to_string())
+                    });
+                match parsed {
+                    Ok(file) => match write_from_triggers(
+                        self.selected_file.clone(),
+                        file,
+                        self.block_scalar_replace,
+                    ) {
+                        Ok(_) => {
+                            self.toast = Some(format!("Formatted {}", self.selected_nav));
+                            return self.update(Message::NavigateTo(self.selected_nav.clone()));
+                        }
+                        Err(err) => self.open_modal(
+                            "Couldn't format file".to_string(),
+                            format!("The file couldn't be written: {}", err),
+                            "OK",
+                            None,
+                        ),
+                    },
+                    Err(err) => self.open_modal(
+                        "Couldn't format file".to_string(),
+                        format!(
+                            "The file couldn't be parsed, so it was left unchanged: {}",
+                            err
+                        ),
+                        "OK",
+                        None,
+                    ),
+                }
+            }
+            PendingAction::DeleteSelectedMatches => self.remove_selected_matches(),
+            PendingAction::MoveSelectedAnyway(destination) => {
+                self.move_selected_to(destination, true)
+            }
+            PendingAction::OverwriteFile => {
+                if self.save_file() {
+                    self.toast = Some(format!("Saved {}", self.selected_file_name()));
+                }
+            }
+            PendingAction::FixCursorHintsAndSave => {
+                self.fix_cursor_hints(None);
+                self.save_file_unless_modified();
+            }
+            PendingAction::SaveWithCursorHints => self.save_file_unless_modified(),
+            PendingAction::OverwriteConfig => self.save_config(),
+            PendingAction::SaveConfigAnyway => {
+                if self.modified_since_load() {
+                    self.show_modified_modal(PendingAction::OverwriteConfig);
+                } else {
+                    self.save_config();
+                }
+            }
+            PendingAction::Reload => {
+                return self.update(Message::NavigateTo(self.selected_nav.clone()));
+            }
+            PendingAction::ReloadAll => {
+                let scan = self.refresh_match_files();
+                if self.selected_nav.is_empty() {
+                    return scan;
+                }
+                // The open file may have been removed or renamed outside the app
+                let destination =
+                    if self.selected_file.as_os_str().is_empty() || self.selected_file.is_file() {
+                        self.selected_nav.clone()
+                    } else {
+                        self.toast = Some(format!("{} no longer exists", self.selected_nav));
+                        "eg-Settings".to_string()
+                    };
+                return Task::batch([scan, self.update(Message::NavigateTo(destination))]);
+            }
+            PendingAction::UseEspansoDir(espanso_dir) => {
+                self.espanso_loc = espanso_dir;
+                return self.activate_espanso_dir();
+            }
+            PendingAction::CreateDefaultConfig(espanso_dir) => {
+                let default_config = Path::new(&espanso_dir).join("config/default.yml");
+                match overwrite_config(&default_config, &ParsedConfig::default()) {
+                    Ok(_) => {
+                        self.espanso_loc = espanso_dir;
+                        return self.activate_espanso_dir();
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to create default config: {}", err);
+                        self.open_modal(
+                            "Couldn't create config".to_string(),
+                            format!("{} couldn't be written: {}", default_config.display(), err),
+                            "OK",
+                            None,
+                        );
+                    }
+                }
+            }
+            PendingAction::RecreateMatchDir => {
+                let match_dir = PathBuf::from(&self.espanso_loc).join("match");
+                match create_dir_all(&match_dir) {
+                    Ok(_) => {
+                        self.toast = Some(format!("Created {}", match_dir.display()));
+                        return self.refresh_match_files();
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to create match folder: {}", err);
+                        self.toast = Some(format!("Couldn't create match folder: {}", err));
+                    }
+                }
+            }
+        }
+
+        Task::none()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let unsaved_changes = self.unsaved_changes();
+        let config_changed = self.config_changed();
         let mut nav_col = column![row![
             text("Files").size(20),
             Tooltip::new(
@@ -622,7 +3383,31 @@ impl EGUI {
                     "Add a new file"
                 },
                 tooltip::Position::Right,
-            )
+            ),
+            Tooltip::new(
+                button(text(icon_to_char(Nerd::FolderOpen)).font(NERD_FONT))
+                    .on_press(Message::OpenExternalFilePressed)
+                    .style(button::text),
+                "Open a match file from anywhere",
+                tooltip::Position::Right,
+            ),
+            Tooltip::new(
+                button(text(icon_to_char(Nerd::Refresh)).font(NERD_FONT))
+                    .on_press_maybe(match self.directory_invalid {
+                        true => None,
+                        false => Some(Message::ReloadAllPressed),
+                    })
+                    .style(button::text),
+                "Reload from disk",
+                tooltip::Position::Right,
+            ),
+            Tooltip::new(
+                button(text(icon_to_char(Nerd::KeyboardOutline)).font(NERD_FONT))
+                    .on_press(Message::ShortcutHelpToggled)
+                    .style(button::text),
+                "Keyboard shortcuts (?)",
+                tooltip::Position::Right,
+            ),
         ]
         .spacing(10)
         .align_y(Alignment::Center)]
@@ -638,28 +3423,208 @@ impl EGUI {
                 left: 10.0,
             });
         for yml_file in &self.match_files {
-            yml_files_col = yml_files_col.push(nav_button(yml_file, yml_file, unsaved_changes));
+            let warning = match self.unopenable_files.get(yml_file) {
+                Some(reason) => Some(format!("Can't be opened: it {}", reason)),
+                None => self
+                    .match_counts
+                    .get(yml_file)
+                    .filter(|count| **count > self.large_file_threshold)
+                    .map(|count| format!("{} matches", count)),
+            };
+            let button = nav_button(
+                row![text(yml_file.as_str())]
+                    .push_maybe(warning.as_ref().map(|_| {
+                        text(icon_to_char(Nerd::AlertOutline))
+                            .font(NERD_FONT)
+                            .style(text::danger)
+                    }))
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                yml_file,
+                unsaved_changes,
+                self.nav_highlight.as_ref() == Some(yml_file),
+            );
+            yml_files_col = yml_files_col.push(match warning {
+                Some(warning) => Element::from(Tooltip::new(
+                    button,
+                    text(warning),
+                    tooltip::Position::Right,
+                )),
+                None => button.into(),
+            });
+        }
+        for yml_file in &self.disabled_match_files {
+            yml_files_col = yml_files_col.push(nav_button(
+                text(format!("{} (off)", yml_file)).style(text::secondary),
+                yml_file,
+                unsaved_changes,
+                self.nav_highlight.as_ref() == Some(yml_file),
+            ));
         }
         if self.show_new_file_input.clone() {
             yml_files_col = yml_files_col.push(
                 text_input("", &self.new_file_name)
                     .on_input(Message::NewFileInputChanged)
                     .on_submit(Message::SubmitNewFileName),
-            )
+            );
+            if !self.match_subfolders.is_empty() {
+                yml_files_col = yml_files_col.push(
+                    pick_list(
+                        [NEW_FILE_ROOT_FOLDER.to_string()]
+                            .into_iter()
+                            .chain(self.match_subfolders.iter().cloned())
+                            .collect::<Vec<String>>(),
+                        Some(if self.new_file_folder.is_empty() {
+                            NEW_FILE_ROOT_FOLDER.to_string()
+                        } else {
+                            self.new_file_folder.clone()
+                        }),
+                        Message::NewFileFolderPicked,
+                    )
+                    .text_size(14),
+                );
+            }
         }
-        nav_col = nav_col.push(yml_files_col);
-        nav_col = nav_col.push(nav_button("Config", "eg-Config", unsaved_changes));
-        nav_col = nav_col.push(nav_button("Settings", "eg-Settings", unsaved_changes));
-        nav_col = nav_col.push(nav_button("About", "eg-About", false));
+        if let Some(found) = self.scan_progress {
+            yml_files_col = yml_files_col.push(
+                text(format!("Scanning... {} found", found))
+                    .size(14)
+                    .style(text::secondary),
+            );
+        }
+        nav_col = nav_col.push(
+            text_input("Find a trigger", &self.trigger_search)
+                .on_input(Message::TriggerSearchInput)
+                .on_submit(Message::TriggerSearchSubmit)
+                .size(14),
+        );
+        nav_col = nav_col.push(match &self.trigger_search_results {
+            Some(results) => {
+                let mut results_col = Column::new().spacing(8).padding(Padding {
+                    top: 0.0,
+                    right: 0.0,
+                    bottom: 0.0,
+                    left: 10.0,
+                });
+                if results.is_empty() {
+                    results_col =
+                        results_col.push(text("No triggers found").size(14).style(text::secondary));
+                }
+                for (stem, triggers) in results {
+                    let expanded = self.expanded_search_file.as_ref() == Some(stem);
+                    results_col = results_col.push(
+                        row![
+                            button(
+                                text(icon_to_char(if expanded {
+                                    Nerd::ChevronDown
+                                } else {
+                                    Nerd::ChevronRight
+                                }))
+                                .font(NERD_FONT)
+                            )
+                            .on_press(Message::SearchResultFileToggled(stem.clone()))
+                            .padding(2)
+                            .style(button::text),
+                            nav_button(
+                                text(format!("{} ({})", stem, triggers.len())),
+                                stem,
+                                unsaved_changes,
+                                self.nav_highlight.as_ref() == Some(stem),
+                            ),
+                        ]
+                        .align_y(Alignment::Center),
+                    );
+                    if expanded {
+                        for trigger in triggers {
+                            results_col = results_col.push(
+                                text(trigger)
+                                    .font(Font::MONOSPACE)
+                                    .size(12)
+                                    .style(text::secondary),
+                            );
+                        }
+                    }
+                }
+                results_col
+            }
+            None => yml_files_col,
+        });
+        let highlighted = |destination: &str| self.nav_highlight.as_deref() == Some(destination);
+        // Ruled off from the match files so a file named "config" isn't
+        // mistaken for espanso's config
+        let section_header = |label| {
+            column![
+                horizontal_rule(1),
+                text(label).size(14).style(text::secondary)
+            ]
+            .spacing(8)
+        };
+        nav_col = nav_col.push(section_header("espanso"));
+        nav_col = nav_col.push(nav_button(
+            row![
+                text(icon_to_char(Nerd::FileCog)).font(NERD_FONT),
+                text("Config")
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            "eg-Config",
+            unsaved_changes,
+            highlighted("eg-Config"),
+        ));
+        nav_col = nav_col.push(section_header("espansoGUI"));
+        nav_col = nav_col.push(nav_button(
+            "Settings",
+            "eg-Settings",
+            unsaved_changes,
+            highlighted("eg-Settings"),
+        ));
+        nav_col = nav_col.push(nav_button(
+            "Logs",
+            "eg-Logs",
+            unsaved_changes,
+            highlighted("eg-Logs"),
+        ));
+        nav_col = nav_col.push(nav_button(
+            "About",
+            "eg-About",
+            false,
+            highlighted("eg-About"),
+        ));
 
         // -- SETTINGS SECTION --
-        let settings_col = column![
-            row![text("Settings").size(25)].padding(Padding {
-                top: 0.0,
-                right: 0.0,
-                bottom: 20.0,
-                left: 0.0,
-            }),
+        let accent_invalid =
+            !self.accent_color.is_empty() && style::parse_accent(&self.accent_color).is_none();
+        let settings_col = column![row![text("Settings").size(25)]]
+        .push_maybe(if self.onboarding {
+            Some(
+                Container::new(
+                    column![
+                        text("Welcome to espansoGUI").size(22),
+                        text("espanso is a text expander: it replaces short triggers you type, like :date, with longer text. espansoGUI edits the files espanso reads its snippets and settings from, so espanso needs to be installed first."),
+                        row![
+                            button("Install espanso").on_press(Message::LaunchURL(
+                                "https://espanso.org/install/".to_string()
+                            )),
+                            button("Find my espanso folder")
+                                .on_press(Message::DetectEspansoDirPressed)
+                                .style(button::secondary),
+                            button("Create a new espanso folder")
+                                .on_press(Message::CreateEspansoDirPressed)
+                                .style(button::secondary),
+                        ]
+                        .spacing(10),
+                        text("If espanso is already installed, its folder is usually found automatically. Otherwise, use Browse below to choose the folder that contains config and match."),
+                    ]
+                    .spacing(15),
+                )
+                .padding(20)
+                .width(Length::Fill)
+                .style(style::gray_background),
+            )
+        } else {
+            None
+        })
+        .push(
             column![
                 row![
                     text("Location").size(20),
@@ -677,6 +3642,27 @@ impl EGUI {
                     ""
                 }),
             ]
+            .push_maybe(if self.recent_dirs.len() > 1 {
+                Some(
+                    row![
+                        text("Recent").size(20),
+                        Space::new(10, 0),
+                        pick_list(
+                            self.recent_dirs.clone(),
+                            self.recent_dirs
+                                .iter()
+                                .find(|dir| **dir == self.espanso_loc)
+                                .cloned(),
+                            Message::RecentDirPicked,
+                        )
+                        .placeholder("Switch to a recent location")
+                        .width(Length::Fill),
+                    ]
+                    .align_y(Alignment::Center),
+                )
+            } else {
+                None
+            })
             .spacing(15)
             .padding(Padding {
                 top: 0.0,
@@ -684,12 +3670,268 @@ impl EGUI {
                 bottom: 0.0,
                 left: 20.0,
             }),
-            Space::new(Length::Fill, Length::Fill),
-            row![
-                Space::new(Length::Fill, 0),
-                button("Save").on_press(Message::SettingsSavePressed)
-            ],
-        ]
+        )
+        .push(
+            column![
+                text("Graphics").size(20),
+                text("These take effect the next time espansoGUI is started. Change them if the app crashes or draws incorrectly on your system."),
+                row![
+                    text("Software renderer").width(200),
+                    toggler(self.software_renderer)
+                        .on_toggle(Message::SoftwareRendererToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text("Present mode").width(200),
+                    pick_list(
+                        PRESENT_MODE_OPTIONS.map(|mode| mode.to_string()).to_vec(),
+                        Some(
+                            self.present_mode
+                                .clone()
+                                .unwrap_or(PRESENT_MODE_OPTIONS[0].to_string())
+                        ),
+                        Message::PresentModePicked,
+                    ),
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text("Antialiasing").width(200),
+                    toggler(self.antialiasing)
+                        .on_toggle(Message::AntialiasingToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(Padding {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 20.0,
+            }),
+        )
+        .push(
+            column![
+                text("Appearance").size(20),
+                row![
+                    text("High contrast").width(200),
+                    toggler(self.high_contrast)
+                        .on_toggle(Message::HighContrastToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+                text("Stronger colors and outlined match cards, following your system's light or dark mode."),
+                row![text("Accent color").width(200)]
+                    .extend(ACCENT_PRESETS.iter().map(|hex| {
+                        let color = style::parse_accent(hex).unwrap_or(Color::BLACK);
+                        button(Space::new(0, 0))
+                            .width(24)
+                            .height(24)
+                            .on_press(Message::AccentColorInput(hex.to_string()))
+                            .style(style::accent_swatch(
+                                color,
+                                self.accent_color.eq_ignore_ascii_case(hex),
+                            ))
+                            .into()
+                    }))
+                    .push(
+                        text_input("#RRGGBB", &self.accent_color)
+                            .on_input(Message::AccentColorInput)
+                            .style(if accent_invalid {
+                                style::invalid_input
+                            } else {
+                                text_input::default
+                            })
+                            .width(100),
+                    )
+                    .push(
+                        button("Default")
+                            .on_press_maybe(
+                                (!self.accent_color.is_empty())
+                                    .then(|| Message::AccentColorInput(String::new())),
+                            )
+                            .style(button::secondary),
+                    )
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(Padding {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 20.0,
+            }),
+        )
+        .push(
+            column![
+                text("Links").size(20),
+                row![
+                    text("Browser command").width(200),
+                    text_input("System default", &self.browser_command)
+                        .on_input(Message::BrowserCommandInput)
+                        .width(300),
+                ]
+                .align_y(Alignment::Center),
+                text("Opens documentation and other links with this command instead of your default browser, e.g. firefox %u. %u is replaced by the link."),
+            ]
+            .spacing(10)
+            .padding(Padding {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 20.0,
+            }),
+        )
+        .push(
+            column![
+                text("Match files").size(20),
+                row![
+                    text("Large file warning").width(200),
+                    number_input(
+                        self.large_file_threshold,
+                        10..100000,
+                        Message::LargeFileThresholdInput
+                    )
+                    .width(Length::Shrink),
+                    text("matches"),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text("Auto-save").width(200),
+                    toggler(self.auto_save)
+                        .on_toggle(Message::AutoSaveToggled)
+                        .width(Length::Shrink),
+                    text("after"),
+                    number_input(self.auto_save_delay, 1..600, Message::AutoSaveDelayInput)
+                        .width(Length::Shrink),
+                    text("seconds without changes"),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text("Largest file to open").width(200),
+                    number_input(self.max_file_size_mb, 1..1000, Message::MaxFileSizeInput)
+                        .width(Length::Shrink),
+                    text("MB"),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                row![
+                    text("Preserve empty matches").width(200),
+                    toggler(self.preserve_empty_matches)
+                        .on_toggle(Message::PreserveEmptyMatchesToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+                text("Load matches with an empty trigger or replace as editable rows, and allow saving them. When off, they're kept as-is but can't be edited."),
+                row![
+                    text("Always use the multi-line editor").width(200),
+                    toggler(self.always_multiline_replace)
+                        .on_toggle(Message::AlwaysMultilineReplaceToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+                text("One-line replacements are edited in a single-line field until you press Enter or open the larger editor. Turn this on to always show the multi-line editor."),
+                row![
+                    text("Warn about extra cursor hints").width(200),
+                    toggler(self.warn_cursor_hints)
+                        .on_toggle(Message::WarnCursorHintsToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+                text("Ask before saving a file where a replacement has more than one $|$, and offer to keep only the first."),
+                row![
+                    text("Write multi-line replacements as blocks").width(200),
+                    toggler(self.block_scalar_replace)
+                        .on_toggle(Message::BlockScalarReplaceToggled)
+                        .width(Length::Shrink),
+                ]
+                .align_y(Alignment::Center),
+                text("Saves replacements that span several lines as an indented | block, which is easier to read and edit by hand. Turn this off to write them on one line with \\n escapes."),
+                row![
+                    button("Check all match files")
+                        .on_press_maybe(
+                            (!self.directory_invalid && self.validation_progress.is_none())
+                                .then_some(Message::ValidateMatchFilesPressed)
+                        )
+                        .style(button::secondary),
+                    match self.validation_progress {
+                        Some(checked) => text(format!("Checking... {} checked", checked))
+                            .style(text::secondary),
+                        None => text("Reads every match file and lists any that can't be loaded or have matches the editor can't show."),
+                    },
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(Padding {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 20.0,
+            }),
+        )
+        .push(
+            SNIPPET_PACKS.iter().enumerate().fold(
+                column![
+                    text("Snippet packs").size(20),
+                    text("Ready-made matches to get started. Adding a pack appends its matches to a file, skipping triggers the file already has."),
+                ]
+                .spacing(10)
+                .padding(Padding {
+                    top: 0.0,
+                    right: 0.0,
+                    bottom: 0.0,
+                    left: 20.0,
+                }),
+                |packs_col, (i, pack)| {
+                    packs_col.push(
+                        row![
+                            text(pack.name).width(200),
+                            text(pack.description)
+                                .style(text::secondary)
+                                .width(Length::Fill),
+                            pick_list(
+                                self.match_files.clone(),
+                                None::<String>,
+                                move |destination| Message::ImportPackTo(i, destination)
+                            )
+                            .placeholder("Add to..."),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                    )
+                },
+            ),
+        )
+        .push(
+            column![
+                text("Export").size(20),
+                text("Save every match file and config as a single JSON document, keyed by path. Nothing in the espanso folder is changed."),
+                button("Export all as JSON").on_press_maybe(if self.directory_invalid {
+                    None
+                } else {
+                    Some(Message::ExportAllPressed)
+                }),
+            ]
+            .spacing(10)
+            .padding(Padding {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 20.0,
+            }),
+        )
+        .push(Space::new(Length::Fill, Length::Fill))
+        .push(row![
+            Space::new(Length::Fill, 0),
+            button("Save").on_press(Message::SettingsSavePressed)
+        ])
+        .spacing(20)
         .padding(20)
         .width(Length::Fill)
         .align_x(Alignment::Start);
@@ -709,77 +3951,697 @@ impl EGUI {
             all_trigger_replace_rows = all_trigger_replace_rows.push(
                 row![
                     button("+ Add").on_press(Message::AddPairPressed),
+                    Tooltip::new(
+                        button(text(icon_to_char(Nerd::ContentPaste)).font(NERD_FONT))
+                            .on_press(Message::PasteYamlPressed)
+                            .style(button::secondary),
+                        "Paste matches from YAML",
+                        tooltip::Position::Bottom,
+                    ),
+                    Tooltip::new(
+                        button(text(icon_to_char(Nerd::ClipboardList)).font(NERD_FONT))
+                            .on_press(Message::PasteListPressed)
+                            .style(button::secondary),
+                        "Paste a list of triggers, one per line or trigger,replace",
+                        tooltip::Position::Bottom,
+                    ),
                     text(format!("Items: {}", self.original_file.matches.len())),
+                    pick_list(
+                        SORT_OPTIONS.map(|option| option.to_string()).to_vec(),
+                        None::<String>,
+                        Message::SortPicked,
+                    )
+                    .placeholder("Sort"),
+                    Tooltip::new(
+                        button(
+                            text(icon_to_char(if self.is_compact_view() {
+                                Nerd::ViewAgenda
+                            } else {
+                                Nerd::ViewHeadline
+                            }))
+                            .font(NERD_FONT)
+                        )
+                        .on_press(Message::CompactViewToggled)
+                        .style(button::secondary),
+                        if self.is_compact_view() {
+                            "Show matches as cards"
+                        } else {
+                            "Show matches as a compact list"
+                        },
+                        tooltip::Position::Bottom,
+                    ),
                     Space::new(Length::Fill, 0),
-                    text_input(&self.file_name_change, &self.file_name_change)
-                        .on_input(Message::FileNameChangeInputChanged)
-                        .on_submit(Message::FileNameChangeSubmit),
-                    text(if self.file_name_change != self.selected_nav {
-                        "Press enter to save changes"
+                    if self.is_external_file() {
+                        // Renaming, disabling and deleting only apply to files in match/
+                        Element::from(
+                            text(self.selected_nav.as_str())
+                                .font(Font::MONOSPACE)
+                                .style(text::secondary),
+                        )
                     } else {
-                        ""
-                    }),
+                        row![
+                            text_input(&self.file_name_change, &self.file_name_change)
+                                .on_input(Message::FileNameChangeInputChanged)
+                                .on_submit(Message::FileNameChangeSubmit),
+                            text(
+                                if self.file_name_change != split_stem(&self.selected_nav).1 {
+                                    "Press enter to save changes"
+                                } else {
+                                    ""
+                                }
+                            ),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                        .into()
+                    },
                     Space::new(Length::Fill, 0),
-                    button(text(icon_to_char(Nerd::TrashOne)).font(NERD_FONT))
-                        .on_press(Message::DeleteFilePressed)
-                        .style(button::danger),
-                    button("Reset").on_press_maybe(
-                        match self.original_file.matches == self.edited_file.matches {
-                            true => None,
-                            false => Some(Message::ResetPressed),
-                        }
+                    Tooltip::new(
+                        toggler(!self.disabled_match_files.contains(&self.selected_nav))
+                            .label("Enabled")
+                            .on_toggle_maybe(
+                                (!self.is_external_file()).then_some(Message::FileEnabledToggled)
+                            )
+                            .width(Length::Shrink),
+                        "Disabled files are kept but ignored by espanso",
+                        tooltip::Position::Bottom,
                     ),
-                    button("Save").on_press_maybe(
-                        match self.original_file.matches == self.edited_file.matches {
-                            true => None,
-                            false => Some(Message::SaveFilePressed),
-                        }
+                    Tooltip::new(
+                        button(text(icon_to_char(Nerd::FormatAlignLeft)).font(NERD_FONT))
+                            .on_press(Message::FormatFilePressed)
+                            .style(button::secondary),
+                        "Format file",
+                        tooltip::Position::Bottom,
+                    ),
+                    Tooltip::new(
+                        button(text(icon_to_char(Nerd::ContentDuplicate)).font(NERD_FONT))
+                            .on_press_maybe(
+                                (!self.is_external_file()).then_some(Message::DuplicateFilePressed)
+                            )
+                            .style(button::secondary),
+                        "Duplicate file",
+                        tooltip::Position::Bottom,
                     ),
+                    Tooltip::new(
+                        button(text(icon_to_char(Nerd::PackageVariant)).font(NERD_FONT))
+                            .on_press(Message::ExportPackagePressed)
+                            .style(button::secondary),
+                        "Export as an espanso package",
+                        tooltip::Position::Bottom,
+                    ),
+                    button(text(icon_to_char(Nerd::TrashOne)).font(NERD_FONT))
+                        .on_press_maybe(
+                            (!self.is_external_file()).then_some(Message::DeleteFilePressed)
+                        )
+                        .style(button::danger),
+                    button("Reset").on_press_maybe(match self.original_file == self.edited_file {
+                        true => None,
+                        false => Some(Message::ResetPressed),
+                    }),
+                    button("Preview")
+                        .on_press(Message::PreviewYamlPressed)
+                        .style(button::secondary),
+                    button("Save").on_press_maybe(match self.original_file == self.edited_file {
+                        true => None,
+                        false => Some(Message::SaveFilePressed),
+                    }),
                 ]
                 .align_y(Alignment::Center)
                 .spacing(10),
             );
 
-            for i in 0..self.edited_file.matches.len() {
+            all_trigger_replace_rows = all_trigger_replace_rows.push(
+                text(if self.governing_configs.is_empty() {
+                    "No config includes this file, so espanso won't load it.".to_string()
+                } else {
+                    format!("Loaded by: {}", self.governing_configs.join(", "))
+                })
+                .style(text::secondary),
+            );
+
+            let count = match_count(&self.edited_file);
+            if count > self.large_file_threshold
+                && !self.disabled_match_files.contains(&self.selected_nav)
+            {
+                all_trigger_replace_rows = all_trigger_replace_rows.push(
+                    text(format!(
+                        "This file has {} matches. espanso can slow down with very large files, so consider splitting it into several smaller ones.",
+                        count
+                    ))
+                    .style(text::danger),
+                );
+            }
+            if let Some(reason) = self.lossy_file {
+                all_trigger_replace_rows = all_trigger_replace_rows
+                    .push(text(format!("This file {}", reason)).style(text::danger));
+            }
+            let untrimmed = self
+                .edited_file
+                .matches
+                .iter()
+                .filter(|a_match| has_untrimmed_trigger(a_match))
+                .count();
+            if untrimmed > 0 {
+                all_trigger_replace_rows = all_trigger_replace_rows.push(
+                    row![
+                        text(format!(
+                            "{} trigger(s) start or end with whitespace and may not fire as intended.",
+                            untrimmed
+                        ))
+                        .style(text::danger),
+                        button("Trim all")
+                            .on_press(Message::TrimAllTriggersPressed)
+                            .style(button::secondary),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                );
+            }
+            if let Some(status) = &self.auto_save_status {
+                all_trigger_replace_rows =
+                    all_trigger_replace_rows.push(text(status).size(14).style(text::secondary));
+            }
+
+            if !self.edited_file.imports.is_empty() {
                 all_trigger_replace_rows = all_trigger_replace_rows.push(
                     Container::new(
-                        row![
-                            button(text(icon_to_char(Nerd::TrashOne)).font(NERD_FONT))
-                                .on_press(Message::DeleteRowPressed(i))
-                                .style(button::text),
+                        column![text("Imports").size(16), text(
+                            "This file also pulls in matches from the files below. Edit them directly to change their matches."
+                        )
+                        .style(text::secondary)]
+                        .extend(self.edited_file.imports.iter().map(|import| {
+                            text(import.as_str()).font(Font::MONOSPACE).into()
+                        }))
+                        .spacing(5),
+                    )
+                    .padding(20)
+                    .width(Length::Fill)
+                    .style(style::gray_background),
+                );
+            }
+
+            all_trigger_replace_rows = all_trigger_replace_rows.push(
+                button(text(format!(
+                    "{} Global variables ({})",
+                    if self.show_global_vars { "▾" } else { "▸" },
+                    self.edited_file.global_vars.len()
+                )))
+                .on_press(Message::GlobalVarsToggled)
+                .style(button::text),
+            );
+            if self.show_global_vars {
+                all_trigger_replace_rows = all_trigger_replace_rows.push(
+                    Container::new(
+                        column![
+                            text("Variables defined here can be used by every match in this file, e.g. {{name}}."),
+                            text_editor(&self.global_vars_te)
+                                .placeholder(
+                                    "- name: greeting\n  type: echo\n  params:\n    echo: Hello"
+                                )
+                                .font(Font::MONOSPACE)
+                                .on_action(Message::EditGlobalVars),
+                        ]
+                        .push_maybe(
+                            self.global_vars_error
+                                .as_ref()
+                                .map(|err| {
+                                    text(format!("Invalid YAML: {}", err)).style(text::danger)
+                                }),
+                        )
+                        .spacing(8),
+                    )
+                    .padding(20)
+                    .width(Length::Fill)
+                    .style(style::gray_background),
+                );
+            }
+
+            if !self.selected_matches.is_empty() {
+                let other_files: Vec<String> = self
+                    .match_files
+                    .iter()
+                    .filter(|file| **file != self.selected_nav)
+                    .cloned()
+                    .collect();
+                all_trigger_replace_rows = all_trigger_replace_rows.push(
+                    row![
+                        text(format!("{} selected", self.selected_matches.len())),
+                        button("Clear")
+                            .on_press(Message::ClearSelectionPressed)
+                            .style(button::secondary),
+                        Space::new(Length::Fill, 0),
+                        button("Copy as YAML")
+                            .on_press(Message::CopySelectedYamlPressed)
+                            .style(button::secondary),
+                        Tooltip::new(
+                            button("Extract variable")
+                                .on_press(Message::ExtractVarPressed)
+                                .style(button::secondary),
+                            "Move text the selected matches share into a global variable",
+                            tooltip::Position::Bottom,
+                        ),
+                        pick_list(other_files, None::<String>, Message::MoveSelectedTo)
+                            .placeholder("Move to..."),
+                        button("Delete")
+                            .on_press(Message::DeleteSelectedPressed)
+                            .style(button::danger),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                );
+            }
+
+            let drop_indicator = || {
+                Container::new(Space::new(Length::Fill, 3))
+                    .width(Length::Fill)
+                    .style(style::drop_indicator)
+            };
+            for i in 0..self.edited_file.matches.len() {
+                // Show where the dragged card would land: above this card when
+                // moving up, below it when moving down
+                let drop_here = match (self.dragging, self.drag_target) {
+                    (Some(from), Some(to)) if to == i && from != i => Some(from < i),
+                    _ => None,
+                };
+                if drop_here == Some(false) {
+                    all_trigger_replace_rows = all_trigger_replace_rows.push(drop_indicator());
+                }
+                // In the compact list, a clicked row opens its full card below it
+                if self.is_compact_view() {
+                    let pair = &self.edited_file.matches[i];
+                    all_trigger_replace_rows = all_trigger_replace_rows.push(
+                        button(
+                            row![
+                                checkbox("", self.selected_matches.contains(&i))
+                                    .on_toggle(move |selected| Message::MatchSelected(i, selected)),
+                                text(pair.trigger.as_str()).font(Font::MONOSPACE).width(250),
+                                text(match &pair.image_path {
+                                    Some(image_path) => format!("Image: {}", image_path),
+                                    None => preview_line(&pair.replace, 80),
+                                })
+                                .style(text::secondary),
+                            ]
+                            .spacing(10)
+                            .align_y(Alignment::Center),
+                        )
+                        .on_press(Message::CompactRowPressed(i))
+                        .width(Length::Fill)
+                        .style(if self.compact_expanded == Some(i) {
+                            button::secondary
+                        } else {
+                            button::text
+                        }),
+                    );
+                    if self.compact_expanded != Some(i) {
+                        continue;
+                    }
+                }
+                all_trigger_replace_rows = all_trigger_replace_rows.push(
+                    mouse_area(
+                        Container::new(
+                            row![
+                            column![
+                                mouse_area(
+                                    text(icon_to_char(Nerd::DragVertical))
+                                        .font(NERD_FONT)
+                                        .size(20)
+                                )
+                                .on_press(Message::DragStarted(i))
+                                .interaction(iced::mouse::Interaction::Grab),
+                                checkbox("", self.selected_matches.contains(&i))
+                                    .on_toggle(move |selected| Message::MatchSelected(i, selected)),
+                                button(text(icon_to_char(Nerd::TrashOne)).font(NERD_FONT))
+                                    .on_press(Message::DeleteRowPressed(i))
+                                    .style(button::text),
+                                Tooltip::new(
+                                    button(text(icon_to_char(Nerd::ContentCopy)).font(NERD_FONT))
+                                        .on_press(Message::CopyMatchYamlPressed(i))
+                                        .style(button::text),
+                                    "Copy as YAML",
+                                    tooltip::Position::Right,
+                                ),
+                                Tooltip::new(
+                                    button(text(icon_to_char(Nerd::Play)).font(NERD_FONT))
+                                        .on_press(Message::TestMatchPressed(i))
+                                        .style(button::text),
+                                    "Test in scratch area",
+                                    tooltip::Position::Right,
+                                ),
+                                Tooltip::new(
+                                    button(
+                                        text(icon_to_char(Nerd::TableRowPlusBefore))
+                                            .font(NERD_FONT)
+                                    )
+                                        .on_press(Message::InsertRow(i))
+                                        .style(button::text),
+                                    "Add a match above",
+                                    tooltip::Position::Right,
+                                ),
+                                Tooltip::new(
+                                    button(
+                                        text(icon_to_char(Nerd::TableRowPlusAfter))
+                                            .font(NERD_FONT)
+                                    )
+                                        .on_press(Message::InsertRow(i + 1))
+                                        .style(button::text),
+                                    "Add a match below",
+                                    tooltip::Position::Right,
+                                ),
+                            ]
+                            .push_maybe(
+                                self.original_file
+                                    .matches
+                                    .get(i)
+                                    .filter(|original| **original != self.edited_file.matches[i])
+                                    .map(|_| {
+                                        Tooltip::new(
+                                            button(
+                                                text(icon_to_char(Nerd::RotateLeft))
+                                                    .font(NERD_FONT),
+                                            )
+                                            .on_press(Message::RevertMatchPressed(i))
+                                            .style(button::text),
+                                            "Revert to saved",
+                                            tooltip::Position::Right,
+                                        )
+                                    })
+                            ),
                             column![
-                                row![
+                                column![row![
                                     text("Trigger:").size(20).width(90),
                                     text_input(
                                         &self.edited_file.matches[i].trigger,
                                         &self.edited_file.matches[i].trigger
                                     )
+                                    .id(trigger_input_id(i))
                                     .on_input(move |new_string| {
                                         Message::YamlInputChanged(
                                             new_string,
                                             i,
                                             "trigger".to_string(),
-                                        )
-                                    })
-                                    .size(20)
-                                ]
-                                .align_y(Alignment::Center),
-                                row![
-                                    text("Replace:").size(20).width(90),
-                                    text_editor(&self.edited_file_te[i]).on_action(move |action| {
-                                        Message::EditReplace(action, i)
+                                        )
                                     })
+                                    .size(20),
+                                    Tooltip::new(
+                                        button(
+                                            text(icon_to_char(Nerd::ClipboardTextOutline))
+                                                .font(NERD_FONT)
+                                        )
+                                        .on_press(Message::CopyTriggerPressed(i))
+                                        .style(button::text),
+                                        "Copy trigger",
+                                        tooltip::Position::Bottom,
+                                    ),
+                                    Tooltip::new(
+                                        button(
+                                            text(icon_to_char(Nerd::SwapVertical))
+                                                .font(NERD_FONT)
+                                        )
+                                        // A trigger can't span lines or be an image
+                                        .on_press_maybe(
+                                            (self.edited_file.matches[i].image_path.is_none()
+                                                && !self.edited_file.matches[i]
+                                                    .replace
+                                                    .contains('\n'))
+                                            .then_some(Message::SwapTriggerReplacePressed(i))
+                                        )
+                                        .style(button::text),
+                                        "Swap trigger and replace",
+                                        tooltip::Position::Bottom,
+                                    ),
+                                    Tooltip::new(
+                                        checkbox("Whole word", self.edited_file.matches[i].word)
+                                            .on_toggle(move |word| Message::WordToggled(i, word)),
+                                        "Only expand when the trigger is typed as a separate word",
+                                        tooltip::Position::Bottom,
+                                    ),
                                 ]
-                                .align_y(Alignment::Center)
+                                .spacing(10)
+                                .align_y(Alignment::Center)]
+                                .push_maybe(self.is_duplicate_trigger(i).then(|| {
+                                    text("Another match in this file uses this trigger")
+                                        .size(14)
+                                        .style(text::danger)
+                                }))
+                                .push_maybe(
+                                    has_untrimmed_trigger(&self.edited_file.matches[i]).then(
+                                        || {
+                                            row![
+                                                text(
+                                                    "This trigger starts or ends with whitespace, which espanso expects to be typed too"
+                                                )
+                                                .size(14)
+                                                .style(text::danger),
+                                                button(text("Trim").size(14))
+                                                    .on_press(Message::TrimTriggerPressed(i))
+                                                    .style(button::text),
+                                            ]
+                                            .spacing(5)
+                                            .align_y(Alignment::Center)
+                                        }
+                                    )
+                                )
+                                .spacing(4),
+                                match &self.edited_file.matches[i].image_path {
+                                    Some(image_path) => row![
+                                        text("Image:").size(20).width(90),
+                                        image(image::Handle::from_path(resolve_image_path(
+                                            image_path,
+                                            &self.espanso_loc,
+                                            &self.selected_file,
+                                        )))
+                                        .height(80),
+                                        text(image_path.as_str()).font(Font::MONOSPACE),
+                                        Space::new(Length::Fill, 0),
+                                        button("Choose...")
+                                            .on_press(Message::PickImagePressed(i))
+                                            .style(button::secondary),
+                                    ]
+                                    .spacing(10)
+                                    .align_y(Alignment::Center),
+                                    None => row![
+                                        text("Replace:").size(20).width(90),
+                                        if self.single_line_replace(i) {
+                                            Element::from(
+                                                text_input("", &self.edited_file.matches[i].replace)
+                                                    .on_input(move |new_string| {
+                                                        Message::YamlInputChanged(
+                                                            new_string,
+                                                            i,
+                                                            "replace".to_string(),
+                                                        )
+                                                    })
+                                                    .on_submit(Message::ReplaceNewlinePressed(i)),
+                                            )
+                                        } else {
+                                            text_editor(&self.edited_file_te[i])
+                                                .on_action(move |action| {
+                                                    Message::EditReplace(action, i)
+                                                })
+                                                .into()
+                                        },
+                                        Tooltip::new(
+                                            button(
+                                                text(icon_to_char(Nerd::ArrowExpand))
+                                                    .font(NERD_FONT)
+                                            )
+                                            .on_press(Message::ExpandReplacePressed(i))
+                                            .style(button::text),
+                                            "Open in a larger editor",
+                                            tooltip::Position::Left,
+                                        ),
+                                        Tooltip::new(
+                                            button(
+                                                text(icon_to_char(Nerd::ClipboardTextOutline))
+                                                    .font(NERD_FONT)
+                                            )
+                                            .on_press(Message::CopyReplacePressed(i))
+                                            .style(button::text),
+                                            "Copy replace text",
+                                            tooltip::Position::Left,
+                                        ),
+                                        Tooltip::new(
+                                            button(
+                                                text(icon_to_char(Nerd::OpenInNew))
+                                                    .font(NERD_FONT)
+                                            )
+                                            .on_press(Message::ExternalEditPressed(i))
+                                            .style(button::text),
+                                            "Edit in external editor",
+                                            tooltip::Position::Left,
+                                        ),
+                                    ]
+                                    .align_y(Alignment::Center),
+                                }
                             ]
+                            .push_maybe(
+                                (extra_cursor_hints(&self.edited_file.matches[i].replace) > 0)
+                                    .then(|| {
+                                        row![
+                                            text(
+                                                "espanso only uses the first $|$ cursor hint, the others will be typed out"
+                                            )
+                                            .size(14)
+                                            .style(text::danger),
+                                            button(text("Keep first").size(14))
+                                                .on_press(Message::FixCursorHintsPressed(i))
+                                                .style(button::text),
+                                        ]
+                                        .spacing(5)
+                                        .align_y(Alignment::Center)
+                                    }),
+                            )
+                            .push_maybe(
+                                (self.edited_file.matches[i].image_path.is_none()
+                                    && quoted_in_yaml(&self.edited_file.matches[i].replace))
+                                .then(|| {
+                                    text(
+                                        "This replacement looks like YAML syntax, so it will be saved in quotes to keep espanso reading it as plain text"
+                                    )
+                                    .size(14)
+                                    .style(text::secondary)
+                                }),
+                            )
+                            .push(
+                                button(text(format!(
+                                    "{} Search terms ({})",
+                                    if self.search_terms_input.contains_key(&i) {
+                                        "▾"
+                                    } else {
+                                        "▸"
+                                    },
+                                    self.edited_file.matches[i].search_terms.len()
+                                )))
+                                .on_press(Message::SearchTermsToggled(i))
+                                .style(button::text),
+                            )
+                            .push_maybe(self.search_terms_input.get(&i).map(|terms| {
+                                text_input("Comma separated, e.g. email, signature", terms)
+                                    .on_input(move |value| Message::SearchTermsInput(i, value))
+                            }))
+                            .push(
+                                text_input(
+                                    "Notes (saved as a comment above the match)",
+                                    &self.edited_file.matches[i].note
+                                )
+                                .on_input(move |value| Message::NoteInput(i, value))
+                                .size(14),
+                            )
                             .spacing(8),
                         ]
-                        .padding(20)
-                        .align_y(Alignment::Center)
-                        .spacing(12),
+                            .padding(20)
+                            .align_y(Alignment::Center)
+                            .spacing(12),
+                        )
+                        .style(if self.round_trip_error == Some(i) {
+                            style::invalid_card
+                        } else {
+                            style::gray_background
+                        }),
                     )
-                    .style(style::gray_background),
+                    .on_enter(Message::DragHovered(i)),
+                );
+                if drop_here == Some(true) {
+                    all_trigger_replace_rows = all_trigger_replace_rows.push(drop_indicator());
+                }
+            }
+            // Always-present blank row; typing a trigger turns it into a real match,
+            // with any replace typed so far, and a new blank row appears below it.
+            // The trigger keeps its focus in the committed card through
+            // text_input::focus.
+            all_trigger_replace_rows = all_trigger_replace_rows.push(
+                Container::new(
+                    row![
+                        button(text(icon_to_char(Nerd::TrashOne)).font(NERD_FONT))
+                            .style(button::text),
+                        column![
+                            row![
+                                text("Trigger:").size(20).width(90),
+                                text_input("New trigger", "")
+                                    .id(trigger_input_id(self.edited_file.matches.len()))
+                                    .on_input(Message::QuickAddTriggerInput)
+                                    .size(20)
+                            ]
+                            .align_y(Alignment::Center),
+                            row![
+                                text("Replace:").size(20).width(90),
+                                text_editor(&self.quick_add_te)
+                                    .placeholder("New replace")
+                                    .on_action(Message::QuickAddReplace)
+                            ]
+                            .align_y(Alignment::Center)
+                        ]
+                        .spacing(8),
+                    ]
+                    .padding(20)
+                    .align_y(Alignment::Center)
+                    .spacing(12),
+                )
+                .style(style::gray_background),
+            );
+            if !self.edited_file.preserved_matches.is_empty() {
+                all_trigger_replace_rows = all_trigger_replace_rows.push(
+                    text(format!(
+                        "{} match(es) can't be edited here because they don't have a single trigger and replace. They will be kept as-is when saving.",
+                        self.edited_file.preserved_matches.len()
+                    )),
                 );
+                for (j, (_, preserved_match)) in
+                    self.edited_file.preserved_matches.iter().enumerate()
+                {
+                    let card: Element<Message> = match &self.raw_match_edit {
+                        Some((editing, content)) if *editing == j => column![
+                            text_editor(content)
+                                .font(Font::MONOSPACE)
+                                .on_action(Message::RawMatchAction),
+                            row![
+                                Space::new(Length::Fill, 0),
+                                button("Cancel")
+                                    .style(button::secondary)
+                                    .on_press(Message::CancelRawMatchPressed),
+                                button("Apply").on_press(Message::ApplyRawMatchPressed),
+                            ]
+                            .spacing(10),
+                        ]
+                        .spacing(10)
+                        .into(),
+                        _ => {
+                            // Forms are summarized, their raw YAML is long and hard to scan
+                            let summary: Element<Message> = match form_field_count(preserved_match)
+                            {
+                                Some(fields) => text(format!(
+                                    "{}: form match with {} field(s)",
+                                    preserved_match
+                                        .get("trigger")
+                                        .and_then(|trigger| trigger.as_str())
+                                        .unwrap_or("No trigger"),
+                                    fields
+                                ))
+                                .into(),
+                                None => {
+                                    text(serde_yaml::to_string(preserved_match).unwrap_or_default())
+                                        .font(Font::MONOSPACE)
+                                        .into()
+                                }
+                            };
+                            row![
+                                summary,
+                                Space::new(Length::Fill, 0),
+                                button("Edit raw")
+                                    .style(button::secondary)
+                                    .on_press(Message::EditRawMatchPressed(j)),
+                            ]
+                            .spacing(10)
+                            .into()
+                        }
+                    };
+                    all_trigger_replace_rows = all_trigger_replace_rows.push(
+                        Container::new(card)
+                            .padding(20)
+                            .width(Length::Fill)
+                            .style(style::gray_background),
+                    );
+                }
             }
             if self.edited_file.matches.len() > 2 {
                 all_trigger_replace_rows = all_trigger_replace_rows.push(
@@ -787,7 +4649,7 @@ impl EGUI {
                         Space::new(Length::Fill, 0),
                         button("+ Add").on_press(Message::AddPairPressed),
                         button("Save").on_press_maybe(
-                            match self.original_file.matches == self.edited_file.matches {
+                            match self.original_file == self.edited_file {
                                 true => None,
                                 false => Some(Message::SaveFilePressed),
                             },
@@ -805,7 +4667,33 @@ impl EGUI {
             bottom: 20.0,
             left: 40.0,
         }))
-        .id(SCROLLABLE_ID.clone())]
+        .id(SCROLLABLE_ID.clone())
+        .on_scroll(|viewport| Message::FileScrolled(viewport.relative_offset()))
+        .height(Length::Fill)]
+        .push_maybe(self.test_output.as_ref().map(|output| {
+            Container::new(
+                column![
+                    row![
+                        text("Scratch area").size(20),
+                        text("Test output appears here. Nothing is sent to espanso.")
+                            .style(text::secondary),
+                        Space::new(Length::Fill, 0),
+                        button("Close")
+                            .on_press(Message::CloseTestOutput)
+                            .style(button::secondary),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    text_editor(output)
+                        .on_action(Message::TestOutputAction)
+                        .height(150),
+                ]
+                .spacing(10),
+            )
+            .padding(20)
+            .width(Length::Fill)
+            .style(style::gray_background)
+        }))
         .width(Length::Fill)
         .align_x(Alignment::Start);
 
@@ -829,27 +4717,25 @@ impl EGUI {
         } else {
             "off".to_string()
         };
-        let word_separators = if !self.temp_word_separators.is_empty() {
-            self.temp_word_separators.to_owned()
-        } else {
-            format!("{:?}", get_default_word_separators())
-        };
-        let keyboard_layout = if self.edited_config.keyboard_layout.is_some() {
-            if self
-                .edited_config
-                .keyboard_layout
-                .clone()
-                .unwrap()
-                .contains_key("layout")
-            {
-                self.edited_config.keyboard_layout.clone().unwrap()["layout"].clone()
-            } else {
-                "us".to_string()
-            }
-        } else {
-            "us".to_string()
-        };
+        let word_separators_error = parse_word_separators(&self.temp_word_separators).err();
+        let word_separators_invalid = word_separators_error.is_some();
+        let keyboard_layout = self
+            .edited_config
+            .keyboard_layout
+            .as_ref()
+            .and_then(|keyboard_layout| keyboard_layout.get("layout")?.as_str())
+            .unwrap_or_default();
 
+        let preset_names: Vec<String> = builtin_presets()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .chain(self.config_presets.keys().cloned())
+            .collect();
+        let preset_name = self.preset_name.trim();
+        let preset_name_valid = !preset_name.is_empty()
+            && !builtin_presets()
+                .iter()
+                .any(|(builtin, _)| *builtin == preset_name);
         let all_config_rows = column!(
             row![
                 Tooltip::new(
@@ -869,29 +4755,46 @@ impl EGUI {
                     .style(button::secondary),
                 Space::new(Length::Fill, 0),
                 Tooltip::new(
-                    button(text(icon_to_char(Nerd::RotateLeft)).font(NERD_FONT))
+                    button(text(icon_to_char(Nerd::Undo)).font(NERD_FONT))
                         .on_press_maybe(
-                            match self.original_config == self.edited_config
-                                && !word_separators_changed
-                            {
-                                true => None,
-                                false => Some(Message::UndoConfigPressed),
-                            }
+                            (!self.config_history.is_empty())
+                                .then_some(Message::UndoConfigStepPressed)
                         )
                         .style(button::secondary),
-                    if self.original_config != self.edited_config || word_separators_changed {
+                    text(match self.config_history.last() {
+                        Some((field, _)) => format!("Undo {}", field.replace('_', " ")),
+                        None => String::new(),
+                    }),
+                    tooltip::Position::Bottom,
+                ),
+                Tooltip::new(
+                    button(text(icon_to_char(Nerd::RotateLeft)).font(NERD_FONT))
+                        .on_press_maybe(match config_changed {
+                            true => Some(Message::UndoConfigPressed),
+                            false => None,
+                        })
+                        .style(button::secondary),
+                    if config_changed {
                         "Undo unsaved changes"
                     } else {
                         ""
                     },
                     tooltip::Position::Bottom,
                 ),
-                button("Save").on_press_maybe(
-                    match self.original_config == self.edited_config && !word_separators_changed {
-                        true => None,
-                        false => Some(Message::SaveConfigPressed),
-                    }
+                Tooltip::new(
+                    button(text(icon_to_char(Nerd::Compare)).font(NERD_FONT))
+                        .on_press(Message::CompareConfigPressed)
+                        .style(button::secondary),
+                    "Compare with another config file",
+                    tooltip::Position::Bottom,
                 ),
+                button("Preview")
+                    .on_press(Message::PreviewYamlPressed)
+                    .style(button::secondary),
+                button("Save").on_press_maybe(match config_changed {
+                    true => Some(Message::SaveConfigPressed),
+                    false => None,
+                }),
             ]
             .align_y(Alignment::Center)
             .spacing(10)
@@ -901,32 +4804,117 @@ impl EGUI {
                 bottom: 20.0,
                 left: 0.0,
             }),
+            column![]
+                .push_maybe((self.edited_config.enable == Some(false)).then(|| {
+                    Container::new(
+                        row![
+                            text(icon_to_char(Nerd::AlertOutline)).font(NERD_FONT).size(20),
+                            text("espanso is turned off, so none of your matches will expand until Enable is turned back on.")
+                                .width(Length::Fill),
+                            button("Turn on").on_press(Message::EnableToggled(true)),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                    )
+                    .padding(15)
+                    .width(Length::Fill)
+                    .style(style::warning_banner)
+                }))
+                .push(
+                    // The options most people change, repeated from the full list below
+                    Container::new(
+                        row![
+                            text("Quick settings").size(18),
+                            Space::new(Length::Fill, 0),
+                            text("Backend"),
+                            pick_list(
+                                config_options(&BACKEND_OPTIONS, &self.edited_config.backend),
+                                config_selection(&BACKEND_OPTIONS, &self.edited_config.backend),
+                                Message::BackendPicked
+                            ),
+                            text("Toggle key"),
+                            pick_list(
+                                config_options(&TOGGLE_KEY_OPTIONS, &self.edited_config.toggle_key),
+                                config_selection(
+                                    &TOGGLE_KEY_OPTIONS,
+                                    &self.edited_config.toggle_key
+                                ),
+                                Message::ToggleKeyPicked
+                            ),
+                            text("Paste shortcut"),
+                            text_input(
+                                if env::consts::OS == "macos" {
+                                    "CMD+V"
+                                } else {
+                                    "CTRL+V"
+                                },
+                                &paste_shortcut,
+                            )
+                            .on_input(Message::PasteShortcutInput)
+                            .width(Length::Fixed(130.0)),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                    )
+                    .padding(15)
+                    .style(style::gray_background),
+                )
+                .spacing(10),
+            row![
+                config_label("Label", "label"),
+                text_input(
+                    "A name for this config",
+                    self.edited_config.label.as_deref().unwrap_or_default()
+                )
+                .on_input(Message::LabelInput)
+                .width(300),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            row![
+                text("Presets").size(20).width(300),
+                pick_list(preset_names, None::<String>, Message::PresetPicked)
+                    .placeholder("Apply a preset"),
+                text_input("Preset name", &self.preset_name)
+                    .on_input(Message::PresetNameInput)
+                    .width(200),
+                button("Save as preset")
+                    .on_press_maybe(match preset_name_valid {
+                        true => Some(Message::SavePresetPressed),
+                        false => None,
+                    })
+                    .style(button::secondary),
+                Tooltip::new(
+                    button(text(icon_to_char(Nerd::TrashOne)).font(NERD_FONT))
+                        .on_press_maybe(
+                            match self.config_presets.contains_key(self.preset_name.trim()) {
+                                true => Some(Message::DeletePresetPressed),
+                                false => None,
+                            }
+                        )
+                        .style(button::danger),
+                    "Delete this preset",
+                    tooltip::Position::Bottom,
+                ),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
             row![
-                text("Backend").size(20).width(300),
+                config_label("Backend", "backend"),
                 pick_list(
-                    vec![
-                        "Auto".to_string(),
-                        "Clipboard".to_string(),
-                        "Inject".to_string(),
-                    ],
-                    if self
-                        .edited_config
-                        .backend
-                        .clone()
-                        .unwrap_or_default()
-                        .is_empty()
-                    {
-                        Some("auto".to_string())
-                    } else {
-                        self.edited_config.backend.clone()
-                    },
+                    config_options(&BACKEND_OPTIONS, &self.edited_config.backend),
+                    config_selection(&BACKEND_OPTIONS, &self.edited_config.backend),
                     Message::BackendPicked
                 )
             ]
+            .push_maybe(unrecognized_option_warning(
+                &BACKEND_OPTIONS,
+                &self.edited_config.backend
+            ))
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Enable").size(20).width(300),
+                config_label("Enable", "enable"),
                 toggler(if self.edited_config.enable.is_some() {
                     self.edited_config.enable.clone().unwrap()
                 } else {
@@ -938,38 +4926,24 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Toggle key").size(20).width(300),
+                config_label("Toggle key", "toggle_key"),
                 pick_list(
-                    vec![
-                        "OFF".to_string(),
-                        "CTRL".to_string(),
-                        "ALT".to_string(),
-                        "SHIFT".to_string(),
-                        "META".to_string(),
-                        "LEFT_CTRL".to_string(),
-                        "LEFT_ALT".to_string(),
-                        "LEFT_SHIFT".to_string(),
-                        "LEFT_META".to_string(),
-                        "RIGHT_CTRL".to_string(),
-                        "RIGHT_ALT".to_string(),
-                        "RIGHT_SHIFT".to_string(),
-                        "RIGHT_META".to_string(),
-                    ],
-                    self.edited_config.toggle_key.clone(),
+                    config_options(&TOGGLE_KEY_OPTIONS, &self.edited_config.toggle_key),
+                    config_selection(&TOGGLE_KEY_OPTIONS, &self.edited_config.toggle_key),
                     Message::ToggleKeyPicked
                 )
             ]
+            .push_maybe(unrecognized_option_warning(
+                &TOGGLE_KEY_OPTIONS,
+                &self.edited_config.toggle_key
+            ))
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Inject delay").size(20).width(300),
+                config_label("Inject delay", "inject_delay"),
                 number_input(
-                    if self.edited_config.inject_delay.is_some() {
-                        self.edited_config.inject_delay.unwrap()
-                    } else {
-                        0
-                    },
-                    0..1000,
+                    config_number(self.edited_config.inject_delay, 0, &DELAY_RANGE),
+                    DELAY_RANGE,
                     Message::InjectDelayInput
                 )
                 .width(Length::Shrink)
@@ -977,14 +4951,10 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Key delay").size(20).width(300),
+                config_label("Key delay", "key_delay"),
                 number_input(
-                    if self.edited_config.key_delay.is_some() {
-                        self.edited_config.key_delay.unwrap()
-                    } else {
-                        0
-                    },
-                    0..1000,
+                    config_number(self.edited_config.key_delay, 0, &DELAY_RANGE),
+                    DELAY_RANGE,
                     Message::KeyDelayInput
                 )
                 .width(Length::Shrink)
@@ -992,14 +4962,14 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Clipboard threshold").size(20).width(300),
+                config_label("Clipboard threshold", "clipboard_threshold"),
                 number_input(
-                    if self.edited_config.clipboard_threshold.is_some() {
-                        self.edited_config.clipboard_threshold.unwrap()
-                    } else {
-                        100
-                    },
-                    0..1000,
+                    config_number(
+                        self.edited_config.clipboard_threshold,
+                        100,
+                        &CLIPBOARD_THRESHOLD_RANGE
+                    ),
+                    CLIPBOARD_THRESHOLD_RANGE,
                     Message::ClipboardThresholdInput
                 )
                 .width(Length::Shrink)
@@ -1007,7 +4977,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Paste shortcut").size(20).width(300),
+                config_label("Paste shortcut", "paste_shortcut"),
                 text_input(
                     if env::consts::OS == "macos" {
                         "CMD+V"
@@ -1022,7 +4992,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Search shortcut").size(20).width(300),
+                config_label("Search shortcut", "search_shortcut"),
                 text_input("ALT+SPACE", &search_shortcut)
                     .on_input(Message::SearchShortcutInput)
                     .width(Length::Fixed(130.0))
@@ -1030,7 +5000,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Search trigger").size(20).width(300),
+                config_label("Search trigger", "search_trigger"),
                 text_input("off", &search_trigger)
                     .on_input(Message::SearchTriggerInput)
                     .width(Length::Fixed(130.0))
@@ -1038,14 +5008,10 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Pre-paste delay").size(20).width(300),
+                config_label("Pre-paste delay", "pre_paste_delay"),
                 number_input(
-                    if self.edited_config.pre_paste_delay.is_some() {
-                        self.edited_config.pre_paste_delay.unwrap()
-                    } else {
-                        300
-                    },
-                    0..1000,
+                    config_number(self.edited_config.pre_paste_delay, 300, &DELAY_RANGE),
+                    DELAY_RANGE,
                     Message::PrePasteDelayInput
                 )
                 .width(Length::Shrink)
@@ -1053,7 +5019,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Disable X11 fast inject").size(20).width(300),
+                config_label("Disable X11 fast inject", "disable_x11_fast_inject"),
                 toggler(if self.edited_config.disable_x11_fast_inject.is_some() {
                     self.edited_config.disable_x11_fast_inject.clone().unwrap()
                 } else {
@@ -1065,14 +5031,10 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Paste shortcut event delay").size(20).width(300),
+                config_label("Paste shortcut event delay", "paste_shortcut_event_delay"),
                 number_input(
-                    if self.edited_config.paste_shortcut_event_delay.is_some() {
-                        self.edited_config.paste_shortcut_event_delay.unwrap()
-                    } else {
-                        10
-                    },
-                    0..1000,
+                    config_number(self.edited_config.paste_shortcut_event_delay, 10, &DELAY_RANGE),
+                    DELAY_RANGE,
                     Message::PasteShortcutEventDelayInput
                 )
                 .width(Length::Shrink)
@@ -1080,7 +5042,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Auto restart").size(20).width(300),
+                config_label("Auto restart", "auto_restart"),
                 toggler(if self.edited_config.auto_restart.is_some() {
                     self.edited_config.auto_restart.clone().unwrap()
                 } else {
@@ -1092,7 +5054,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Preserve clipboard").size(20).width(300),
+                config_label("Preserve clipboard", "preserve_clipboard"),
                 toggler(if self.edited_config.preserve_clipboard.is_some() {
                     self.edited_config.preserve_clipboard.clone().unwrap()
                 } else {
@@ -1104,14 +5066,10 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Restore clipboard delay").size(20).width(300),
+                config_label("Restore clipboard delay", "restore_clipboard_delay"),
                 number_input(
-                    if self.edited_config.restore_clipboard_delay.is_some() {
-                        self.edited_config.restore_clipboard_delay.unwrap()
-                    } else {
-                        300
-                    },
-                    0..1000,
+                    config_number(self.edited_config.restore_clipboard_delay, 300, &DELAY_RANGE),
+                    DELAY_RANGE,
                     Message::RestoreClipboardDelayInput
                 )
                 .width(Length::Shrink)
@@ -1119,14 +5077,10 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("EVDEV modifier delay").size(20).width(300),
+                config_label("EVDEV modifier delay", "evdev_modifier_delay"),
                 number_input(
-                    if self.edited_config.evdev_modifier_delay.is_some() {
-                        self.edited_config.evdev_modifier_delay.unwrap()
-                    } else {
-                        10
-                    },
-                    0..1000,
+                    config_number(self.edited_config.evdev_modifier_delay, 10, &DELAY_RANGE),
+                    DELAY_RANGE,
                     Message::EvdevModifierDelayInput
                 )
                 .width(Length::Shrink)
@@ -1134,25 +5088,27 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Word separators").size(20).width(300),
+                config_label("Word separators", "word_separators"),
                 text_input(
-                    &format!("{:?}", get_default_word_separators()),
-                    &word_separators
+                    &serde_json::to_string(&get_default_word_separators()).unwrap_or_default(),
+                    &self.temp_word_separators
                 )
                 .on_input(Message::WordSeparatorsInput)
+                .style(move |theme, status| if word_separators_invalid {
+                    style::invalid_input(theme, status)
+                } else {
+                    text_input::default(theme, status)
+                })
                 .width(Length::Fixed(130.0))
             ]
+            .push_maybe(word_separators_error.map(|err| text(err).size(14).style(text::danger)))
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Backspace limit").size(20).width(300),
+                config_label("Backspace limit", "backspace_limit"),
                 number_input(
-                    if self.edited_config.backspace_limit.is_some() {
-                        self.edited_config.backspace_limit.unwrap()
-                    } else {
-                        5
-                    },
-                    0..100,
+                    config_number(self.edited_config.backspace_limit, 5, &BACKSPACE_LIMIT_RANGE),
+                    BACKSPACE_LIMIT_RANGE,
                     Message::BackspaceLimitInput
                 )
                 .width(Length::Shrink)
@@ -1160,7 +5116,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Apply patch").size(20).width(300),
+                config_label("Apply patch", "apply_patch"),
                 toggler(if self.edited_config.apply_patch.is_some() {
                     self.edited_config.apply_patch.clone().unwrap()
                 } else {
@@ -1172,15 +5128,15 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Keyboard layout").size(20).width(300),
-                text_input("us", &keyboard_layout)
+                config_label("Keyboard layout", "keyboard_layout"),
+                text_input("us", keyboard_layout)
                     .on_input(Message::KeyboardLayoutInput)
                     .width(Length::Fixed(130.0))
             ]
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Undo backspace").size(20).width(300),
+                config_label("Undo backspace", "undo_backspace"),
                 toggler(if self.edited_config.undo_backspace.is_some() {
                     self.edited_config.undo_backspace.clone().unwrap()
                 } else {
@@ -1192,7 +5148,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Show notifications").size(20).width(300),
+                config_label("Show notifications", "show_notifications"),
                 toggler(if self.edited_config.show_notifications.is_some() {
                     self.edited_config.show_notifications.clone().unwrap()
                 } else {
@@ -1204,7 +5160,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Show icon").size(20).width(300),
+                config_label("Show icon", "show_icon"),
                 toggler(if self.edited_config.show_icon.is_some() {
                     self.edited_config.show_icon.clone().unwrap()
                 } else {
@@ -1216,7 +5172,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("X11 use xclip backend").size(20).width(300),
+                config_label("X11 use xclip backend", "x11_use_xclip_backend"),
                 toggler(if self.edited_config.x11_use_xclip_backend.is_some() {
                     self.edited_config.x11_use_xclip_backend.clone().unwrap()
                 } else {
@@ -1228,7 +5184,7 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Win32 exclude orphan events").size(20).width(300),
+                config_label("Win32 exclude orphan events", "win32_exclude_orphan_events"),
                 toggler(
                     if self.edited_config.win32_exclude_orphan_events.is_some() {
                         self.edited_config
@@ -1245,22 +5201,17 @@ impl EGUI {
             .spacing(10)
             .align_y(Alignment::Center),
             row![
-                text("Win32 keyboard layout cache interval")
-                    .size(20)
-                    .width(300),
+                config_label(
+                    "Win32 keyboard layout cache interval",
+                    "win32_keyboard_layout_cache_interval"
+                ),
                 number_input(
-                    if self
-                        .edited_config
-                        .win32_keyboard_layout_cache_interval
-                        .is_some()
-                    {
-                        self.edited_config
-                            .win32_keyboard_layout_cache_interval
-                            .unwrap()
-                    } else {
-                        2000
-                    },
-                    0..10000,
+                    config_number(
+                        self.edited_config.win32_keyboard_layout_cache_interval,
+                        2000,
+                        &LAYOUT_CACHE_INTERVAL_RANGE
+                    ),
+                    LAYOUT_CACHE_INTERVAL_RANGE,
                     Message::KeyboardLayoutCacheIntervalInput
                 )
                 .width(Length::Shrink)
@@ -1286,6 +5237,62 @@ impl EGUI {
         .width(Length::Fill)
         .align_x(Alignment::Start);
 
+        // -- LOGS SECTION --
+        let logs_col = column![
+            row![
+                text("espanso Logs").size(25),
+                Space::new(Length::Fill, 0),
+                button("Copy")
+                    .on_press(Message::CopyLogsPressed)
+                    .style(button::secondary),
+                button(if self.logs_loading {
+                    "Loading..."
+                } else {
+                    "Refresh"
+                })
+                .on_press_maybe(if self.logs_loading {
+                    None
+                } else {
+                    Some(Message::RefreshLogsPressed)
+                }),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            text("Output of `espanso log`. Include it when reporting a problem.")
+                .style(text::secondary),
+            text_editor(&self.logs)
+                .font(Font::MONOSPACE)
+                .on_action(Message::LogsAction)
+                .height(Length::Fill),
+        ]
+        .spacing(15)
+        .padding(20)
+        .width(Length::Fill);
+
+        // -- FOLDER ERROR SECTION --
+        let dir_error_col = column![
+            text("espanso folder unavailable").size(25),
+            text(format!(
+                "{} can't be found or is missing its config and match folders. It may have been moved or deleted, or be on a drive or network share that isn't connected right now.",
+                self.espanso_loc
+            )),
+            row![
+                button("Try again").on_press(Message::RetryEspansoDirPressed),
+                button("Choose another folder")
+                    .on_press(Message::NavigateTo("eg-Settings".to_string()))
+                    .style(button::secondary),
+                button("Recreate it")
+                    .on_press(Message::CreateEspansoDirPressed)
+                    .style(button::secondary),
+            ]
+            .spacing(10),
+            text("Recreating starts a new, empty espanso folder at this location.")
+                .style(text::secondary),
+        ]
+        .spacing(15)
+        .padding(20)
+        .width(Length::Fill);
+
         // -- ABOUT SECTION --
         let about_col = column![
                     row![text("About").size(25)].padding(Padding {
@@ -1329,6 +5336,13 @@ impl EGUI {
                         row![
                             text("espansoGUI is under active development and may not be perfect. Please backup your espanso directory before using this program to modify any files.")
                         ].padding([0,40]),
+                        Tooltip::new(
+                            button("Copy debug info")
+                                .on_press(Message::CopyDebugInfoPressed)
+                                .style(button::secondary),
+                            "Copy your OS, versions, espanso directory and recent messages to paste into a bug report",
+                            tooltip::Position::Bottom,
+                        ),
                     ]
                     .spacing(15)
                     .align_x(Alignment::Center),
@@ -1354,64 +5368,561 @@ impl EGUI {
             match self.selected_nav.as_str() {
                 "eg-Settings" => settings_col,
                 "eg-Config" => config_col,
+                "eg-Logs" => logs_col,
                 "eg-About" => about_col,
+                "eg-DirError" => dir_error_col,
                 _ => open_file_col,
             }
         ];
 
-        let underlay = Container::new(main_row)
+        let underlay = Container::new(column![main_row].push_maybe(self.toast.as_ref().map(
+            |toast| {
+                Container::new(
+                    row![text(toast), Space::new(Length::Fill, 0)]
+                        // Only while the deletion's own toast is showing
+                        .push_maybe(
+                            self.deleted_file
+                                .as_ref()
+                                .filter(|(stem, _, _)| *toast == format!("Deleted {}", stem))
+                                .map(|_| {
+                                    button("Undo")
+                                        .on_press(Message::UndoDeletePressed)
+                                        .style(button::secondary)
+                                }),
+                        )
+                        .push(
+                            button("x")
+                                .on_press(Message::DismissToast)
+                                .style(button::text),
+                        )
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                )
+                .padding([5, 20])
+                .width(Length::Fill)
+                .style(style::gray_background)
+            },
+        )))
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let overlay: Option<Card<'_, Message, Theme, Renderer>> = if self.show_modal.clone() {
+            Some(
+                Card::new(text(&self.modal_title), text(&self.modal_description))
+                    .foot(
+                        row![
+                            button(text("Cancel").align_x(alignment::Horizontal::Center))
+                                .width(Length::Fill)
+                                .on_press(Message::ModalCancelPressed),
+                        ]
+                        .push_maybe(self.modal_alt_action.as_ref().map(|(alt_text, _)| {
+                            button(text(alt_text).align_x(alignment::Horizontal::Center))
+                                .width(Length::Fill)
+                                .style(button::secondary)
+                                .on_press(Message::ModalAltPressed)
+                        }))
+                        .push(
+                            button(
+                                text(&self.modal_ok_text).align_x(alignment::Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .style(
+                                if self
+                                    .modal_action
+                                    .as_ref()
+                                    .is_some_and(PendingAction::is_destructive)
+                                {
+                                    button::danger
+                                } else {
+                                    button::primary
+                                },
+                            )
+                            .on_press(Message::ModalOkPressed),
+                        )
+                        .spacing(10)
+                        .padding(5)
+                        .width(Length::Fill),
+                    )
+                    .max_width(300.0)
+                    .on_close(Message::CloseModal),
+            )
+        } else {
+            None
+        };
+
+        let expanded_editor = self
+            .expanded_replace
+            .filter(|i| *i < self.edited_file_te.len())
+            .map(|i| {
+                container(
+                    column![
+                        row![
+                            text(format!(
+                                "Replace for {}",
+                                self.edited_file.matches[i].trigger
+                            ))
+                            .size(20),
+                            Space::new(Length::Fill, 0),
+                            button("Done").on_press(Message::CloseExpandedReplace),
+                        ]
+                        .align_y(Alignment::Center),
+                        text_editor(&self.edited_file_te[i])
+                            .on_action(move |action| Message::EditReplace(action, i))
+                            .height(Length::Fill),
+                    ]
+                    .spacing(10),
+                )
+                .padding(20)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(container::rounded_box)
+            });
+
+        let yaml_preview = self.yaml_preview.as_ref().map(|preview| {
+            container(
+                column![
+                    row![
+                        text("YAML to be written").size(20),
+                        Space::new(Length::Fill, 0),
+                        button("Close")
+                            .on_press(Message::CloseYamlPreview)
+                            .style(button::secondary),
+                        button("Save").on_press(Message::YamlPreviewSavePressed),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    text("Nothing is written until you press Save.").style(text::secondary),
+                    text_editor(preview)
+                        .font(Font::MONOSPACE)
+                        .on_action(Message::YamlPreviewAction)
+                        .height(Length::Fill),
+                ]
+                .spacing(10),
+            )
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(container::rounded_box)
+        });
+
+        let config_comparison = self
+            .config_comparison
+            .as_ref()
+            .map(|(other_name, differences)| {
+                let cell = |value: &str| {
+                    text(value.to_string())
+                        .font(Font::MONOSPACE)
+                        .width(Length::FillPortion(2))
+                };
+                let mut rows = column![row![
+                    text("Option").width(Length::FillPortion(1)),
+                    text("This config").width(Length::FillPortion(2)),
+                    text(other_name).width(Length::FillPortion(2)),
+                ]
+                .spacing(10)]
+                .spacing(10);
+                for difference in differences {
+                    rows = rows.push(
+                        row![
+                            text(&difference.option).width(Length::FillPortion(1)),
+                            cell(&difference.edited),
+                            cell(&difference.other),
+                        ]
+                        .spacing(10),
+                    );
+                }
+                container(
+                    column![
+                        row![
+                            text(format!("Compared with {}", other_name)).size(20),
+                            Space::new(Length::Fill, 0),
+                            button("Close")
+                                .on_press(Message::CloseConfigComparison)
+                                .style(button::secondary),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                        text(if differences.is_empty() {
+                            "Both configs set the same options.".to_string()
+                        } else {
+                            format!(
+                                "{} option(s) differ. Unsaved changes here are included.",
+                                differences.len()
+                            )
+                        })
+                        .style(text::secondary),
+                        scrollable(rows).height(Length::Fill),
+                    ]
+                    .spacing(10),
+                )
+                .padding(20)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(container::rounded_box)
+            });
+
+        let validation_report = self.validation_report.as_ref().map(|report| {
+            let mut rows = column![].spacing(12);
+            for (stem, problem) in report {
+                rows = rows.push(
+                    column![
+                        nav_button(
+                            text(stem).font(Font::MONOSPACE),
+                            stem,
+                            unsaved_changes,
+                            false
+                        ),
+                        text(problem),
+                    ]
+                    .spacing(4),
+                );
+            }
+            container(
+                column![
+                    row![
+                        text("Match file check").size(20),
+                        Space::new(Length::Fill, 0),
+                        button("Close")
+                            .on_press(Message::CloseValidationReport)
+                            .style(button::secondary),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    text(if report.is_empty() {
+                        "Every match file loaded without problems.".to_string()
+                    } else {
+                        format!("{} file(s) need a look.", report.len())
+                    })
+                    .style(text::secondary),
+                    scrollable(rows).height(Length::Fill),
+                ]
+                .spacing(10),
+            )
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(container::rounded_box)
+        });
+
+        let shortcut_help = self.show_shortcuts.then(|| {
+            let shortcut_rows = |in_dialog: bool| {
+                Column::with_children(
+                    SHORTCUTS
+                        .iter()
+                        .filter(|shortcut| shortcut.in_dialog == in_dialog)
+                        .map(|shortcut| {
+                            row![
+                                text(shortcut.keys()).font(Font::MONOSPACE).width(150),
+                                text(shortcut.action),
+                            ]
+                            .into()
+                        }),
+                )
+                .spacing(6)
+            };
+            container(
+                column![
+                    row![
+                        text("Keyboard shortcuts").size(20),
+                        Space::new(Length::Fill, 0),
+                        button("Close")
+                            .on_press(Message::ShortcutHelpToggled)
+                            .style(button::secondary),
+                    ]
+                    .align_y(Alignment::Center),
+                    text("Main window").style(text::secondary),
+                    shortcut_rows(false),
+                    text("In dialogs").style(text::secondary),
+                    shortcut_rows(true),
+                ]
+                .spacing(12),
+            )
+            .padding(20)
+            .max_width(450)
+            .style(container::rounded_box)
+        });
+
+        let extract_var = self.extract_var.as_ref().map(|(shared, name)| {
+            let placeholder = format!("{{{{{}}}}}", name);
+            let name_taken =
+                self.edited_file.global_vars.iter().any(|var| {
+                    var.get("name").and_then(|var_name| var_name.as_str()) == Some(name)
+                });
+            let mut changes = column![].spacing(10);
+            let mut changed = 0;
+            for i in &self.selected_matches {
+                let Some(a_match) = self.edited_file.matches.get(*i) else {
+                    continue;
+                };
+                if shared.is_empty() || !a_match.replace.contains(shared.as_str()) {
+                    continue;
+                }
+                changed += 1;
+                changes = changes.push(
+                    column![
+                        text(&a_match.trigger).size(18),
+                        text(a_match.replace.replace(shared.as_str(), &placeholder))
+                            .font(Font::MONOSPACE),
+                    ]
+                    .spacing(4),
+                );
+            }
+            let problem = if !is_var_name(name) {
+                Some("Variable names may only contain letters, numbers and underscores.")
+            } else if name_taken {
+                Some("This file already has a global variable with that name.")
+            } else if changed == 0 {
+                Some("None of the selected matches contain this text.")
+            } else {
+                None
+            };
+            container(
+                column![
+                    row![
+                        text("Extract a shared variable").size(20),
+                        Space::new(Length::Fill, 0),
+                        button("Cancel")
+                            .on_press(Message::CloseExtractVar)
+                            .style(button::secondary),
+                        button("Apply").on_press_maybe(match problem {
+                            None => Some(Message::ApplyExtractVarPressed),
+                            Some(_) => None,
+                        }),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        text("Text").width(90),
+                        text_input("Text the matches share", shared)
+                            .on_input(Message::ExtractVarTextInput),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        text("Variable").width(90),
+                        text_input("name", name).on_input(Message::ExtractVarNameInput),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    text(problem.map(str::to_string).unwrap_or_else(|| format!(
+                        "{} match(es) will use {}. Nothing is saved until you save the file.",
+                        changed, placeholder
+                    )))
+                    .style(if problem.is_some() {
+                        text::danger
+                    } else {
+                        text::secondary
+                    }),
+                    scrollable(changes).height(Length::Fill),
+                ]
+                .spacing(10),
+            )
+            .padding(20)
             .width(Length::Fill)
-            .height(Length::Fill);
+            .height(Length::Fill)
+            .style(container::rounded_box)
+        });
 
-        let overlay: Option<Card<'_, Message, Theme, Renderer>> = if self.show_modal.clone() {
-            Some(
-                Card::new(text(&self.modal_title), text(&self.modal_description))
-                    .foot(
-                        row![
-                            button(text("Cancel").align_x(alignment::Horizontal::Center))
-                                .width(Length::Fill)
-                                .on_press(Message::ModalCancelPressed),
-                            button(
-                                text(&self.modal_ok_text).align_x(alignment::Horizontal::Center)
-                            )
-                            .width(Length::Fill)
-                            .style(if self.modal_ok_text == "Delete" {
-                                button::danger
-                            } else {
-                                button::primary
-                            })
-                            .on_press(Message::ModalOkPressed),
-                        ]
-                        .spacing(10)
-                        .padding(5)
-                        .width(Length::Fill),
+        let paste_list = self.paste_list.as_ref().map(|(triggers, replace)| {
+            container(
+                column![
+                    row![
+                        text(format!("Add {} match(es)", triggers.len())).size(20),
+                        Space::new(Length::Fill, 0),
+                        button("Cancel")
+                            .on_press(Message::ClosePasteList)
+                            .style(button::secondary),
+                        button("Add").on_press_maybe(
+                            (!replace.is_empty()).then_some(Message::ApplyPasteListPressed)
+                        ),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        text("Replace").width(90),
+                        text_input("Text every trigger below will expand to", replace)
+                            .on_input(Message::PasteListReplaceInput)
+                            .on_submit_maybe(
+                                (!replace.is_empty()).then_some(Message::ApplyPasteListPressed)
+                            ),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    text("Triggers already in this file are skipped.").style(text::secondary),
+                    scrollable(
+                        Column::with_children(
+                            triggers
+                                .iter()
+                                .map(|trigger| text(trigger).font(Font::MONOSPACE).into()),
+                        )
+                        .spacing(4),
                     )
-                    .max_width(300.0)
-                    .on_close(Message::CloseModal),
+                    .height(Length::Fill),
+                ]
+                .spacing(10),
             )
-        } else {
-            None
-        };
+            .padding(20)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(container::rounded_box)
+        });
 
         if let Some(alert) = overlay {
             modal(underlay, container(alert), Message::CloseModal).into()
+        } else if let Some(paste_list) = paste_list {
+            modal(
+                underlay,
+                container(paste_list)
+                    .padding([40, 0])
+                    .width(Length::FillPortion(10)),
+                Message::ClosePasteList,
+            )
+        } else if let Some(extract_var) = extract_var {
+            modal(
+                underlay,
+                container(extract_var)
+                    .padding([40, 0])
+                    .width(Length::FillPortion(10)),
+                Message::CloseExtractVar,
+            )
+        } else if let Some(help) = shortcut_help {
+            modal(underlay, help, Message::ShortcutHelpToggled)
+        } else if let Some(report) = validation_report {
+            modal(
+                underlay,
+                container(report)
+                    .padding([40, 0])
+                    .width(Length::FillPortion(10)),
+                Message::CloseValidationReport,
+            )
+        } else if let Some(comparison) = config_comparison {
+            modal(
+                underlay,
+                container(comparison)
+                    .padding([40, 0])
+                    .width(Length::FillPortion(10)),
+                Message::CloseConfigComparison,
+            )
+        } else if let Some(preview) = yaml_preview {
+            modal(
+                underlay,
+                container(preview)
+                    .padding([40, 0])
+                    .width(Length::FillPortion(10)),
+                Message::CloseYamlPreview,
+            )
+        } else if let Some(editor) = expanded_editor {
+            modal(
+                underlay,
+                container(editor)
+                    .padding([40, 0])
+                    .width(Length::FillPortion(10)),
+                Message::CloseExpandedReplace,
+            )
         } else {
             underlay.into()
         }
     }
 }
 
-fn get_app_dir() -> PathBuf {
+// Collapses repeated separators and drops trailing ones, keeping the root,
+// e.g. "/home/me//espanso/" -> "/home/me/espanso". On Windows either slash
+// separates and the result uses backslashes, with a leading \\ kept for
+// network paths.
+fn normalize_dir_path(path: &str, windows: bool) -> String {
+    let path = path.trim();
+    let (separator, is_separator): (char, fn(char) -> bool) = if windows {
+        ('\\', |c| c == '\\' || c == '/')
+    } else {
+        ('/', |c| c == '/')
+    };
+    let network = windows && path.len() > 2 && path.chars().take(2).all(is_separator);
+    let rooted = path.starts_with(is_separator);
+    let parts: Vec<&str> = path
+        .split(is_separator)
+        .filter(|part| !part.is_empty())
+        .collect();
+    let mut normalized = parts.join(&separator.to_string());
+    if network {
+        normalized.insert_str(0, r"\\");
+    } else if rooted {
+        normalized.insert(0, separator);
+    }
+    // A bare drive keeps its separator, since "C:" means the current folder on C
+    if windows && normalized.len() == 2 && normalized.ends_with(':') {
+        normalized.push(separator);
+    }
+    normalized
+}
+
+// Portable installs can keep settings and backups together in one place
+fn app_dir_override() -> Option<PathBuf> {
+    env::var_os("ESPANSO_GUI_DATA_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+// Settings live here
+fn get_app_config_dir() -> PathBuf {
+    if let Some(app_dir) = app_dir_override() {
+        return app_dir;
+    }
     if let Some(config_dir) = config_dir() {
+        // Linux: $XDG_CONFIG_HOME/espansoGUI or ~/.config/espansoGUI
         // Mac: /Users/username/Library/Application Support/espansoGUI
+        // Windows: C:\Users\username\AppData\Roaming\espansoGUI
         return config_dir.join("espansoGUI");
     } else {
         return PathBuf::from("./");
     }
 }
 
-fn read_egui_data() -> Result<EGUIData, Box<dyn std::error::Error>> {
-    let path_to_file = get_app_dir().join("egui_data.json");
+// Backups and anything else that isn't a setting live here
+fn get_app_data_dir() -> PathBuf {
+    if let Some(app_dir) = app_dir_override() {
+        return app_dir;
+    }
+    if let Some(data_dir) = data_local_dir() {
+        // Linux: $XDG_DATA_HOME/espansoGUI or ~/.local/share/espansoGUI
+        // Mac: /Users/username/Library/Application Support/espansoGUI
+        // Windows: C:\Users\username\AppData\Local\espansoGUI
+        return data_dir.join("espansoGUI");
+    } else {
+        return get_app_config_dir();
+    }
+}
+
+// Builds without a resolvable config dir wrote egui_data.json to the working
+// directory. Move it to the config dir once one is available.
+fn migrate_egui_data(path_to_file: &Path) {
+    let legacy_file = PathBuf::from("./egui_data.json");
+    if path_to_file.exists()
+        || !legacy_file.is_file()
+        || config_dir().is_none()
+        || app_dir_override().is_some()
+    {
+        return;
+    }
+    if let Some(parent) = path_to_file.parent() {
+        if let Err(err) = create_dir_all(parent) {
+            eprintln!("Failed to create directory: {}", err);
+            return;
+        }
+    }
+    // rename fails across filesystems, so fall back to copy and remove
+    if rename(&legacy_file, path_to_file).is_err() {
+        match copy(&legacy_file, path_to_file) {
+            Ok(_) => {
+                let _ = remove_file(&legacy_file);
+            }
+            Err(err) => eprintln!("Failed to migrate egui_data.json: {}", err),
+        }
+    }
+}
+
+pub(crate) fn read_egui_data() -> Result<EGUIData, Box<dyn std::error::Error>> {
+    let path_to_file = get_app_config_dir().join("egui_data.json");
+    migrate_egui_data(&path_to_file);
     let mut file = File::open(path_to_file)?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
@@ -1420,16 +5931,99 @@ fn read_egui_data() -> Result<EGUIData, Box<dyn std::error::Error>> {
     Ok(data)
 }
 
+// Like read_egui_data, but a corrupt file is set aside instead of silently
+// replaced, and as much of it as possible is recovered. Returns a message for
+// the user when something had to be recovered.
+fn load_egui_data() -> (EGUIData, Option<String>) {
+    let default_data = EGUIData {
+        espanso_dir: get_default_espanso_dir(),
+        ..Default::default()
+    };
+    let path_to_file = get_app_config_dir().join("egui_data.json");
+    let contents = match read_egui_data() {
+        Ok(data) => return (data, None),
+        Err(_) if !path_to_file.is_file() => return (default_data, None),
+        Err(err) => {
+            eprintln!("Couldn't read egui_data.json: {}", err);
+            std::fs::read(&path_to_file)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_default()
+        }
+    };
+
+    // Set the bad file aside so the next save doesn't back it up over the good backup
+    let corrupt_file = get_app_data_dir().join("egui_data.json.corrupt");
+    let set_aside = create_dir_all(get_app_data_dir())
+        .and_then(|_| copy(&path_to_file, &corrupt_file))
+        .and_then(|_| remove_file(&path_to_file));
+    if let Err(err) = set_aside {
+        eprintln!("Failed to set aside egui_data.json: {}", err);
+    }
+
+    let salvaged = salvage_espanso_dir(&contents).or_else(|| {
+        std::fs::read_to_string(get_app_data_dir().join("egui_data.json.bak"))
+            .ok()
+            .and_then(|backup| serde_json::from_str::<EGUIData>(&backup).ok())
+            .map(|backup| backup.espanso_dir)
+            .filter(|dir| !dir.is_empty())
+    });
+    match salvaged {
+        Some(espanso_dir) => (
+            EGUIData {
+                espanso_dir,
+                ..default_data
+            },
+            Some(format!(
+                "Settings file was damaged and has been reset. Your espanso directory was recovered. The old file is at {}",
+                corrupt_file.display()
+            )),
+        ),
+        None => (
+            default_data,
+            Some(format!(
+                "Settings file was damaged and has been reset. The old file is at {}",
+                corrupt_file.display()
+            )),
+        ),
+    }
+}
+
+// Pull espanso_dir out of a file that no longer parses as a whole
+fn salvage_espanso_dir(contents: &str) -> Option<String> {
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(contents) {
+        return value
+            .get("espanso_dir")
+            .and_then(|dir| dir.as_str())
+            .map(String::from)
+            .filter(|dir| !dir.is_empty());
+    }
+    let pattern = Regex::new(r#""espanso_dir"\s*:\s*("(?:[^"\\]|\\.)*")"#).unwrap();
+    pattern
+        .captures(contents)
+        .and_then(|captures| serde_json::from_str::<String>(&captures[1]).ok())
+        .filter(|dir| !dir.is_empty())
+}
+
 fn write_egui_data(data: &EGUIData) -> Result<(), Box<dyn std::error::Error>> {
-    let directory = get_app_dir();
+    let directory = get_app_config_dir();
     if !directory.is_dir() {
-        match create_dir(directory.clone()) {
+        match create_dir_all(directory.clone()) {
             Ok(_) => println!("App directory created successfully."),
             Err(err) => eprintln!("Failed to create directory: {}", err),
         }
     }
     let path_to_file = directory.join("egui_data.json");
 
+    // Keep the previous settings around in case the new ones turn out bad
+    if path_to_file.is_file() {
+        let backup_dir = get_app_data_dir();
+        if create_dir_all(&backup_dir).is_ok() {
+            if let Err(err) = copy(&path_to_file, backup_dir.join("egui_data.json.bak")) {
+                eprintln!("Failed to back up egui_data.json: {}", err);
+            }
+        }
+    }
+
     let mut file = OpenOptions::new()
         .write(true)
         .create(true)
@@ -1441,27 +6035,93 @@ fn write_egui_data(data: &EGUIData) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn read_to_triggers(path: PathBuf) -> EspansoYaml {
-    let file = File::open(path.clone()).expect("Could not open file.");
-    let yaml: EspansoYaml = serde_yaml::from_reader(file).expect("Could not read values.");
-    let filtered_yaml: Vec<YamlPairs> = yaml
-        .matches
-        .into_iter()
-        .filter(|pair| !pair.trigger.is_empty() && !pair.replace.is_empty())
-        .collect();
-    EspansoYaml {
-        matches: filtered_yaml,
+// Opening a huge or binary file dropped into match/ by mistake would hang the
+// parser, so these are refused with a reason that follows the file name
+fn check_match_file(path: &Path, max_file_size_mb: u64) -> Result<(), String> {
+    if let Some(reason) = file_too_large(path, max_file_size_mb) {
+        return Err(reason);
+    }
+    let (yaml, _) = read_yaml_text(path).map_err(|err| format!("couldn't be read: {}", err))?;
+    if looks_binary(&yaml) {
+        return Err(BINARY_FILE_REASON.to_string());
     }
+    Ok(())
 }
 
-fn write_from_triggers(path: PathBuf, edited_file: EspansoYaml) {
-    let file = OpenOptions::new()
+fn file_too_large(path: &Path, max_file_size_mb: u64) -> Option<String> {
+    let size = metadata(path).ok()?.len();
+    (size > max_file_size_mb * 1024 * 1024).then(|| {
+        format!(
+            "is {:.1} MB, more than the {} MB limit set in Settings.",
+            size as f64 / (1024.0 * 1024.0),
+            max_file_size_mb
+        )
+    })
+}
+
+// Text files never contain NUL; UTF-16 files are already decoded by now
+fn looks_binary(text: &str) -> bool {
+    text.contains('\0')
+}
+
+// Also returns a warning about how the file was read and whether saving it
+// would lose anything
+// The first match that wouldn't read back the same after saving. Notes are
+// left out since they're trimmed on the way out and back in, which loses nothing.
+fn first_changed_by_round_trip(
+    file: &EspansoYaml,
+    block_replace: bool,
+    keep_empty_matches: bool,
+) -> Option<usize> {
+    let reparsed = file
+        .to_yaml_with(block_replace)
+        .ok()
+        .and_then(|yaml| EspansoYaml::parse(&yaml, keep_empty_matches).ok())
+        .map(|file| file.matches)
+        .unwrap_or_default();
+    file.matches.iter().enumerate().position(|(i, a_match)| {
+        reparsed.get(i).map(|reread| YamlPairs {
+            note: a_match.note.clone(),
+            ..reread.clone()
+        }) != Some(a_match.clone())
+    })
+}
+
+// Like try_read_to_triggers, with the error worded to follow the file name
+fn open_match_file(
+    path: &Path,
+    keep_empty_matches: bool,
+) -> Result<(EspansoYaml, Option<String>, Option<&'static str>), String> {
+    try_read_to_triggers(path, keep_empty_matches)
+        .map_err(|err| format!("couldn't be parsed: {}", err))
+}
+
+fn try_read_to_triggers(
+    path: &Path,
+    keep_empty_matches: bool,
+) -> Result<(EspansoYaml, Option<String>, Option<&'static str>), String> {
+    let (yaml, warning) = read_yaml_text(path).map_err(|err| err.to_string())?;
+    // Matches that aren't simple trigger/replace pairs are split off into
+    // preserved_matches rather than dropped, so they survive a save
+    let file = EspansoYaml::parse(&yaml, keep_empty_matches).map_err(|err| err.to_string())?;
+    let lossy = file.lossy_reason(&yaml);
+    Ok((file, warning, lossy))
+}
+
+fn write_from_triggers(
+    path: PathBuf,
+    edited_file: EspansoYaml,
+    block_replace: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Serialize first so a failure can't leave the file truncated
+    let serialized = edited_file.to_yaml_with(block_replace)?;
+    let mut file = OpenOptions::new()
         .write(true)
         .truncate(true)
         .create(true)
-        .open(path)
-        .expect("Couldn't open file");
-    serde_yaml::to_writer(file, &edited_file).unwrap();
+        .open(path)?;
+    file.write_all(serialized.as_bytes())?;
+    Ok(())
 }
 
 fn create_new_yml_file(file_path: PathBuf) {
@@ -1474,14 +6134,481 @@ fn create_new_yml_file(file_path: PathBuf) {
     serde_yaml::to_writer(file, &EspansoYaml::default()).unwrap();
 }
 
-fn overwrite_config(path: &Path, config: &ParsedConfig) {
-    let file = OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .create(true)
-        .open(path)
-        .expect("Couldn't write config to file");
-    serde_yaml::to_writer(file, config).unwrap();
+// The value a numeric option's input shows: its default when unset, and a
+// value typed into the file outside the input's range pulled back into it
+fn config_number<T: Copy + PartialOrd>(
+    value: Option<T>,
+    default: T,
+    range: &RangeInclusive<T>,
+) -> T {
+    let value = value.unwrap_or(default);
+    if value < *range.start() {
+        *range.start()
+    } else if value > *range.end() {
+        *range.end()
+    } else {
+        value
+    }
+}
+
+// Options set differently in the two configs, where an unset option reads "Not set"
+fn config_differences(edited: &ParsedConfig, other: &ParsedConfig) -> Vec<ConfigDifference> {
+    let options = |config: &ParsedConfig| match serde_yaml::to_value(config) {
+        Ok(serde_yaml::Value::Mapping(options)) => options,
+        _ => serde_yaml::Mapping::new(),
+    };
+    let (edited, other) = (options(edited), options(other));
+    let shown = |value: Option<&serde_yaml::Value>| match value {
+        None | Some(serde_yaml::Value::Null) => "Not set".to_string(),
+        Some(value) => serde_yaml::to_string(value)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    };
+    let mut differences = Vec::new();
+    for (key, value) in &edited {
+        let other_value = other.get(key);
+        if shown(Some(value)) != shown(other_value) {
+            differences.push(ConfigDifference {
+                option: key.as_str().unwrap_or_default().to_string(),
+                edited: shown(Some(value)),
+                other: shown(other_value),
+            });
+        }
+    }
+    differences
+}
+
+// Unset options are left out instead of written as null, and lists the file
+// wrote as [a, b] are written that way again
+fn config_yaml(config: &ParsedConfig) -> Result<String, serde_yaml::Error> {
+    let mut value = serde_yaml::to_value(config)?;
+    if let serde_yaml::Value::Mapping(options) = &mut value {
+        options.retain(|_, value| !value.is_null());
+    }
+    let serialized = serde_yaml::to_string(&value)?;
+
+    let mut yaml = String::new();
+    let mut lines = serialized.lines().peekable();
+    while let Some(line) = lines.next() {
+        let flow_sequence = line
+            .strip_suffix(':')
+            .filter(|key| config.flow_sequences.contains(*key))
+            .and_then(|key| Some((key, value.get(key)?.as_sequence()?)));
+        match flow_sequence {
+            Some((key, items)) => {
+                let items: Option<Vec<String>> = items
+                    .iter()
+                    .map(|item| item.as_str().map(flow_scalar))
+                    .collect();
+                let Some(items) = items else {
+                    yaml.push_str(line);
+                    yaml.push('\n');
+                    continue;
+                };
+                yaml.push_str(&format!("{}: [{}]\n", key, items.join(", ")));
+                // Skip the block sequence, including multi-line items
+                while lines.next_if(|line| line.starts_with(['-', ' '])).is_some() {}
+            }
+            None => {
+                yaml.push_str(line);
+                yaml.push('\n');
+            }
+        }
+    }
+    Ok(yaml)
+}
+
+// Plain when YAML would read it back unchanged, otherwise double quoted.
+// JSON strings are valid double quoted YAML.
+fn flow_scalar(item: &str) -> String {
+    let plain = !item.is_empty()
+        && item.trim() == item
+        && !item.contains([',', '[', ']', '{', '}', '#', ':', '"', '\''])
+        && serde_yaml::from_str::<serde_yaml::Value>(item)
+            .is_ok_and(|value| value.as_str() == Some(item));
+    if plain {
+        item.to_string()
+    } else {
+        serde_json::to_string(item).unwrap_or_default()
+    }
+}
+
+fn overwrite_config(path: &Path, config: &ParsedConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // Serialize first so a failure can't leave the file truncated
+    let yaml = config_yaml(config)?;
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(path)?;
+    file.write_all(yaml.as_bytes())?;
+    Ok(())
+}
+
+// Config files whose includes/excludes pull in match_file, described with the
+// filters that decide which apps they apply to
+fn configs_including(espanso_loc: &str, match_file: &Path) -> Vec<String> {
+    let config_dir = Path::new(espanso_loc).join("config");
+    let Ok(entries) = read_dir(&config_dir) else {
+        return Vec::new();
+    };
+    let mut config_files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        })
+        .collect();
+    config_files.sort();
+
+    let match_file = slash_path(&normalize_path(match_file));
+    let matches_any = |patterns: &[String]| {
+        patterns.iter().any(|pattern| {
+            let pattern = slash_path(&normalize_path(&config_dir.join(pattern)));
+            glob_to_regex(&pattern).is_some_and(|regex| regex.is_match(&match_file))
+        })
+    };
+
+    let mut governing = Vec::new();
+    for config_file in config_files {
+        let Ok(config) = ParsedConfig::load(&config_file) else {
+            continue;
+        };
+        // Same defaults espanso uses
+        let mut includes = Vec::new();
+        let mut excludes = vec!["../match/**/_*.yml".to_string()];
+        if config.use_standard_includes.unwrap_or(true) {
+            includes.push("../match/**/[!_]*.yml".to_string());
+        }
+        includes.extend(config.includes.into_iter().flatten());
+        includes.extend(config.extra_includes.into_iter().flatten());
+        excludes.extend(config.excludes.into_iter().flatten());
+        excludes.extend(config.extra_excludes.into_iter().flatten());
+        if !matches_any(&includes) || matches_any(&excludes) {
+            continue;
+        }
+
+        let name = config_file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let filters: Vec<String> = [
+            ("title", config.filter_title),
+            ("class", config.filter_class),
+            ("exec", config.filter_exec),
+            ("os", config.filter_os),
+        ]
+        .into_iter()
+        .filter_map(|(filter, value)| value.map(|value| format!("{} {}", filter, value)))
+        .collect();
+        governing.push(if name == "default.yml" {
+            format!("{} (all apps)", name)
+        } else if filters.is_empty() {
+            format!("{} (no filter)", name)
+        } else {
+            format!("{} ({})", name, filters.join(", "))
+        });
+    }
+    governing
+}
+
+// Resolves . and .. without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn slash_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+// Enough of espanso's glob syntax for include patterns: **, *, ? and [..]
+fn glob_to_regex(pattern: &str) -> Option<Regex> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '^' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+// A hub package name, e.g. "My Emails" -> "my-emails"
+fn package_name_for(stem: &str) -> String {
+    let name = stem
+        .split(|character: char| !character.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+        .to_lowercase();
+    if name.is_empty() {
+        "my-snippets".to_string()
+    } else {
+        name
+    }
+}
+
+// Lays out package_dir the way the espanso hub expects a package version,
+// with a manifest and README to fill in before publishing
+fn scaffold_package(
+    package_dir: &Path,
+    name: &str,
+    file: &EspansoYaml,
+    block_replace: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    create_dir_all(package_dir)?;
+    write_from_triggers(package_dir.join("package.yml"), file.clone(), block_replace)?;
+
+    let title = name
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut manifest = serde_yaml::Mapping::new();
+    for (key, value) in [
+        ("name", name.to_string()),
+        ("title", title.clone()),
+        (
+            "description",
+            "A short description of these snippets".to_string(),
+        ),
+        ("version", "0.1.0".to_string()),
+        ("author", "Your name".to_string()),
+    ] {
+        manifest.insert(key.into(), value.into());
+    }
+    manifest.insert("tags".into(), serde_yaml::Value::Sequence(Vec::new()));
+    std::fs::write(
+        package_dir.join("_manifest.yml"),
+        serde_yaml::to_string(&manifest)?,
+    )?;
+
+    let mut readme = format!(
+        "# {}\n\nA short description of these snippets.\n\n## Installation\n\n```\nespanso install {}\n```\n\n## Triggers\n\n",
+        title, name
+    );
+    for a_match in &file.matches {
+        readme.push_str(&format!(
+            "- `{}`: {}\n",
+            a_match.trigger,
+            match a_match.image_path {
+                Some(_) => "an image".to_string(),
+                None => preview_line(&a_match.replace, 60),
+            }
+        ));
+    }
+    std::fs::write(package_dir.join("README.md"), readme)?;
+    Ok(())
+}
+
+// Every match file and config in one JSON document, keyed by path relative
+// to the espanso dir. Files that don't parse are listed with their error.
+fn export_espanso_dir(espanso_dir: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let mut export = BTreeMap::new();
+    for entry in WalkDir::new(espanso_dir.join("match"))
+        .into_iter()
+        .chain(WalkDir::new(espanso_dir.join("config")))
+        .filter_map(|entry| entry.ok())
+    {
+        let path = entry.path();
+        let is_yaml = path
+            .extension()
+            .is_some_and(|ext| ext == "yml" || ext == "yaml");
+        if !entry.file_type().is_file() || !is_yaml {
+            continue;
+        }
+        let key = slash_path(path.strip_prefix(espanso_dir)?);
+        let is_config = key.starts_with("config/");
+        let parsed = if is_config {
+            ParsedConfig::load(path)
+                .map_err(|err| err.to_string())
+                .and_then(|config| serde_json::to_value(config).map_err(|err| err.to_string()))
+                .map(|mut config| {
+                    // Leave out options that aren't set
+                    if let Some(options) = config.as_object_mut() {
+                        options.retain(|_, value| !value.is_null());
+                    }
+                    config
+                })
+        } else {
+            read_yaml_text(path)
+                .map_err(|err| err.to_string())
+                .and_then(|(yaml, _)| {
+                    serde_yaml::from_str::<EspansoYaml>(&yaml).map_err(|err| err.to_string())
+                })
+                .and_then(|file| serde_json::to_value(file).map_err(|err| err.to_string()))
+        };
+        let value = match parsed {
+            Ok(value) => value,
+            Err(err) => serde_json::json!({ "error": err }),
+        };
+        export.insert(key, value);
+    }
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+// Writes text to a temporary file, opens it in $VISUAL or $EDITOR (or the
+// platform's text editor) and reads it back once the editor exits. Runs on its
+// own thread since the editor can stay open for a long time.
+async fn edit_in_external_editor(text: String) -> Result<String, String> {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(run_external_editor(&text));
+    });
+    receiver
+        .await
+        .unwrap_or_else(|_| Err("The editor stopped unexpectedly.".to_string()))
+}
+
+fn run_external_editor(text: &str) -> Result<String, String> {
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let path = env::temp_dir().join(format!("espanso-gui-{}-{}.txt", std::process::id(), stamp));
+    std::fs::write(&path, text)
+        .map_err(|err| format!("Couldn't create a temporary file: {}", err))?;
+
+    // Editors are often set with arguments, e.g. "code --wait"
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty());
+    let mut command = match editor {
+        Some(editor) => {
+            let mut parts = editor.split_whitespace();
+            let mut command = std::process::Command::new(parts.next().unwrap_or_default());
+            command.args(parts);
+            command
+        }
+        None if cfg!(target_os = "windows") => std::process::Command::new("notepad"),
+        None if cfg!(target_os = "macos") => {
+            let mut command = std::process::Command::new("open");
+            command.args(["-W", "-t"]);
+            command
+        }
+        None => {
+            let _ = remove_file(&path);
+            return Err(
+                "No editor is set. Set the EDITOR environment variable, e.g. to \"code --wait\", and restart espansoGUI."
+                    .to_string(),
+            );
+        }
+    };
+
+    let status = command.arg(&path).status();
+    let result = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&path).map_err(|_| {
+            "The temporary file was deleted or unreadable after the editor closed, so nothing was changed.".to_string()
+        }),
+        Ok(status) => Err(format!("The editor exited with {}, so nothing was changed.", status)),
+        Err(err) => Err(format!("Couldn't start the editor: {}", err)),
+    };
+    let _ = remove_file(&path);
+    result
+}
+
+fn espanso_version() -> String {
+    match std::process::Command::new("espanso")
+        .arg("--version")
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        Ok(output) => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        Err(_) => "not found on PATH".to_string(),
+    }
+}
+
+// Ticks once a second for as long as the subscription is kept
+fn every_second() -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(1, |mut output| async move {
+        let (mut sender, mut receiver) = iced::futures::channel::mpsc::channel(1);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs(1));
+            if sender.try_send(()).is_err_and(|err| err.is_disconnected()) {
+                break;
+            }
+        });
+        while receiver.next().await.is_some() {
+            let _ = output.send(Message::AutoSaveTick).await;
+        }
+    })
+}
+
+// Recent lines from `espanso log`, or why they couldn't be read
+fn read_espanso_log() -> String {
+    const MAX_LINES: usize = 500;
+    match std::process::Command::new("espanso").arg("log").output() {
+        Ok(output) => {
+            let mut log = String::from_utf8_lossy(&output.stdout).into_owned();
+            if !output.status.success() {
+                log.push_str(&String::from_utf8_lossy(&output.stderr));
+            }
+            let lines: Vec<&str> = log.lines().collect();
+            let recent = lines[lines.len().saturating_sub(MAX_LINES)..].join("\n");
+            if recent.trim().is_empty() {
+                "espanso log returned nothing.".to_string()
+            } else {
+                recent
+            }
+        }
+        Err(err) => format!(
+            "Couldn't run espanso log: {}\nMake sure espanso is installed and on your PATH.",
+            err
+        ),
+    }
+}
+
+fn get_modified_time(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|meta| meta.modified()).ok()
 }
 
 fn get_default_espanso_dir() -> String {
@@ -1493,6 +6620,36 @@ fn get_default_espanso_dir() -> String {
     String::new()
 }
 
+// Lays out the same structure espanso creates on first run
+fn create_espanso_dir(espanso_dir: &Path) -> std::io::Result<()> {
+    create_dir_all(espanso_dir.join("config"))?;
+    create_dir_all(espanso_dir.join("match"))?;
+    let default_config = espanso_dir.join("config/default.yml");
+    if !default_config.exists() {
+        File::create(default_config)?.write_all(b"# espanso configuration file\n")?;
+    }
+    let base_matches = espanso_dir.join("match/base.yml");
+    if !base_matches.exists() {
+        let base_yaml = EspansoYaml {
+            matches: vec![YamlPairs {
+                trigger: ":espanso".to_string(),
+                replace: "Hi there!".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let file = File::create(base_matches)?;
+        serde_yaml::to_writer(file, &base_yaml).map_err(std::io::Error::other)?;
+    }
+    Ok(())
+}
+
+fn add_recent_dir(recent_dirs: &mut Vec<String>, dir: &str) {
+    recent_dirs.retain(|recent| recent != dir);
+    recent_dirs.insert(0, dir.to_string());
+    recent_dirs.truncate(MAX_RECENT_DIRS);
+}
+
 fn valid_espanso_dir(selected_dir: String) -> bool {
     // Check if expected directories and files exist to verify it is valid
     let selected_dir: PathBuf = PathBuf::from(selected_dir);
@@ -1508,25 +6665,141 @@ fn valid_espanso_dir(selected_dir: String) -> bool {
     }
 }
 
-fn get_all_match_file_stems(match_dir: PathBuf) -> Vec<String> {
+// Walks match/ on its own thread so a big or slow directory doesn't block the
+// UI, reporting how many files were found along the way
+fn scan_match_files(
+    match_dir: PathBuf,
+    max_file_size_mb: u64,
+) -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(100, move |mut output| async move {
+        let (done_sender, done_receiver) = iced::futures::channel::oneshot::channel();
+        let mut progress = output.clone();
+        let scan_dir = match_dir.clone();
+        let (counts_sender, counts_receiver) = iced::futures::channel::oneshot::channel();
+        std::thread::spawn(move || {
+            let match_files = get_all_match_file_stems(scan_dir.clone(), |found| {
+                if found % 25 == 0 {
+                    // Dropping an update when the UI is behind is fine
+                    let _ = progress.try_send(Message::ScanProgress(found));
+                }
+            });
+            let disabled_match_files = get_disabled_match_file_stems(scan_dir.clone());
+            let _ = done_sender.send((match_files.clone(), disabled_match_files));
+            // Counting needs every file parsed, so the nav fills in first
+            let mut match_counts = BTreeMap::new();
+            let mut unopenable_files = BTreeMap::new();
+            for stem in match_files {
+                let path = scan_dir.join(format!("{}.yml", stem));
+                if let Some(reason) = file_too_large(&path, max_file_size_mb) {
+                    unopenable_files.insert(stem, reason);
+                    continue;
+                }
+                let Ok((yaml, _)) = read_yaml_text(&path) else {
+                    continue;
+                };
+                if looks_binary(&yaml) {
+                    unopenable_files.insert(stem, BINARY_FILE_REASON.to_string());
+                    continue;
+                }
+                let count = serde_yaml::from_str::<serde_yaml::Value>(&yaml)
+                    .ok()
+                    .and_then(|value| Some(value.get("matches")?.as_sequence()?.len()));
+                if let Some(count) = count {
+                    match_counts.insert(stem, count);
+                }
+            }
+            let _ = counts_sender.send((match_counts, unopenable_files));
+        });
+        let (match_files, disabled_match_files) = done_receiver.await.unwrap_or_default();
+        let _ = output
+            .send(Message::ScanFinished(
+                match_dir.clone(),
+                match_files,
+                disabled_match_files,
+            ))
+            .await;
+        if let Ok((match_counts, unopenable_files)) = counts_receiver.await {
+            let _ = output
+                .send(Message::MatchCountsFound(
+                    match_dir,
+                    match_counts,
+                    unopenable_files,
+                ))
+                .await;
+        }
+    })
+}
+
+// Reads every file in match/ on its own thread, reporting progress, and
+// finishes with the problems found in each
+fn validate_match_files(
+    match_dir: PathBuf,
+    max_file_size_mb: u64,
+    keep_empty_matches: bool,
+) -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(100, move |mut output| async move {
+        let (done_sender, done_receiver) = iced::futures::channel::oneshot::channel();
+        let mut progress = output.clone();
+        let check_dir = match_dir.clone();
+        std::thread::spawn(move || {
+            let mut report = Vec::new();
+            let stems = get_all_match_file_stems(check_dir.clone(), |_| {});
+            for (checked, stem) in stems.into_iter().enumerate() {
+                if checked % 25 == 0 {
+                    // Dropping an update when the UI is behind is fine
+                    let _ = progress.try_send(Message::ValidationProgress(checked));
+                }
+                let path = check_dir.join(format!("{}.yml", stem));
+                if let Some(problem) =
+                    match_file_problem(&path, max_file_size_mb, keep_empty_matches)
+                {
+                    report.push((stem, problem));
+                }
+            }
+            let _ = done_sender.send(report);
+        });
+        let report = done_receiver.await.unwrap_or_default();
+        let _ = output
+            .send(Message::ValidationFinished(match_dir, report))
+            .await;
+    })
+}
+
+fn match_file_problem(
+    path: &Path,
+    max_file_size_mb: u64,
+    keep_empty_matches: bool,
+) -> Option<String> {
+    match check_match_file(path, max_file_size_mb) {
+        Err(reason) => Some(format!("Can't be opened: it {}", reason)),
+        Ok(_) => match try_read_to_triggers(path, keep_empty_matches) {
+            Err(err) => Some(format!("Couldn't be read: {}", err)),
+            Ok((file, _, _)) if !file.preserved_matches.is_empty() => Some(format!(
+                "{} match(es) use features the editor can't show, like forms or regex triggers. They're kept as-is.",
+                file.preserved_matches.len()
+            )),
+            Ok(_) => None,
+        },
+    }
+}
+
+// Stems are relative to match/ so files in subfolders read as folder/name
+fn get_all_match_file_stems(match_dir: PathBuf, mut on_found: impl FnMut(usize)) -> Vec<String> {
     let mut match_file_stems = Vec::new();
     // Walk the directory and get all .yml file names
-    for entry in WalkDir::new(match_dir)
+    for entry in WalkDir::new(&match_dir)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|e| e.depth() == 0 || e.file_name() != DISABLED_DIR)
         .filter_map(|e| e.ok())
     {
         if entry.path().is_file() {
             if let Some(extension) = entry.path().extension() {
                 if extension == "yml" {
-                    match_file_stems.push(
-                        entry
-                            .path()
-                            .file_stem()
-                            .unwrap()
-                            .to_string_lossy()
-                            .into_owned(),
-                    );
+                    if let Ok(relative) = entry.path().strip_prefix(&match_dir) {
+                        match_file_stems.push(slash_path(&relative.with_extension("")));
+                        on_found(match_file_stems.len());
+                    }
                 }
             }
         }
@@ -1535,37 +6808,718 @@ fn get_all_match_file_stems(match_dir: PathBuf) -> Vec<String> {
     match_file_stems
 }
 
+// Subfolders of match/ new files can be created in, relative to match/
+fn get_match_subfolders(match_dir: &Path) -> Vec<String> {
+    let mut subfolders: Vec<String> = WalkDir::new(match_dir)
+        .follow_links(true)
+        .min_depth(1)
+        .into_iter()
+        // espanso skips anything starting with _, which includes _disabled
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('_'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .filter_map(|e| e.path().strip_prefix(match_dir).ok().map(slash_path))
+        .collect();
+    subfolders.sort();
+    subfolders
+}
+
+// Splits a stem into its folder under match/ (empty at the top level) and name
+fn split_stem(stem: &str) -> (&str, &str) {
+    stem.rsplit_once('/').unwrap_or(("", stem))
+}
+
+fn join_stem(folder: &str, name: &str) -> String {
+    if folder.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", folder, name)
+    }
+}
+
+// Inverse of match_file_path: _disabled/_name.yml is name and
+// _disabled/_folder/name.yml is folder/name
+fn get_disabled_match_file_stems(match_dir: PathBuf) -> Vec<String> {
+    let mut disabled_file_stems = Vec::new();
+    let disabled_dir = match_dir.join(DISABLED_DIR);
+    for entry in WalkDir::new(&disabled_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "yml") {
+            if let Ok(relative) = path.strip_prefix(&disabled_dir) {
+                let stem = slash_path(&relative.with_extension(""));
+                if let Some(stem) = stem.strip_prefix('_') {
+                    disabled_file_stems.push(stem.to_string());
+                }
+            }
+        }
+    }
+    disabled_file_stems.sort();
+
+    disabled_file_stems
+}
+
+// The espanso dir a file is in and where to navigate to open it: its stem for
+// a match file, or the Config screen for config/default.yml
+fn locate_espanso_file(path: &Path) -> Option<(String, String)> {
+    let path = std::path::absolute(path).ok()?;
+    if path.extension().is_none_or(|extension| extension != "yml") {
+        return None;
+    }
+    let espanso_dir = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| valid_espanso_dir(dir.display().to_string()))?;
+    let destination = if path == espanso_dir.join("config").join("default.yml") {
+        "eg-Config".to_string()
+    } else {
+        let stem = slash_path(
+            &path
+                .strip_prefix(espanso_dir.join("match"))
+                .ok()?
+                .with_extension(""),
+        );
+        match stem.strip_prefix(&format!("{}/_", DISABLED_DIR)) {
+            Some(disabled_stem) => disabled_stem.to_string(),
+            None => stem,
+        }
+    };
+    Some((espanso_dir.display().to_string(), destination))
+}
+
+// Every match in the file, including ones the GUI can't edit
+fn match_count(file: &EspansoYaml) -> usize {
+    file.matches.len() + file.preserved_matches.len()
+}
+
+fn match_file_path(espanso_loc: &str, stem: &str, disabled: bool) -> PathBuf {
+    let match_dir = PathBuf::from(espanso_loc).join("match");
+    if disabled {
+        match_dir.join(DISABLED_DIR).join(format!("_{}.yml", stem))
+    } else {
+        match_dir.join(format!("{}.yml", stem))
+    }
+}
+
 fn nav_button<'a>(
-    text: &'a str,
+    label: impl Into<Element<'a, Message>>,
     destination: &'a str,
     unsaved_changes: bool,
+    highlighted: bool,
 ) -> Button<'a, Message> {
-    button(text)
+    button(label)
         .on_press({
             if unsaved_changes {
-                Message::ShowModal(
-                    "Unsaved Changes".to_string(),
-                    "Leaving this file with erase any unsaved changes.".to_string(),
-                    destination.to_string(),
-                )
+                Message::ConfirmLeaveFile(PendingAction::Navigate(destination.to_string()))
             } else {
                 Message::NavigateTo(destination.to_string())
             }
         })
-        .style(button::text)
+        .style(if highlighted {
+            button::secondary
+        } else {
+            button::text
+        })
 }
 
-fn is_valid_file_name(file_name: &str) -> bool {
+// Roughly what espanso would type for a replace: echo global vars are
+// substituted, the $|$ cursor marker is dropped and anything else is left as-is
+fn render_replace(replace: &str, global_vars: &[serde_yaml::Value]) -> String {
+    let mut rendered = replace.replace("$|$", "");
+    for var in global_vars {
+        let (Some(name), Some("echo")) = (
+            var.get("name").and_then(|name| name.as_str()),
+            var.get("type").and_then(|var_type| var_type.as_str()),
+        ) else {
+            continue;
+        };
+        if let Some(echo) = var
+            .get("params")
+            .and_then(|params| params.get("echo"))
+            .and_then(|echo| echo.as_str())
+        {
+            rendered = rendered.replace(&format!("{{{{{}}}}}", name), echo);
+        }
+    }
+    rendered
+}
+
+// espanso expands $CONFIG to the espanso dir; other relative paths are taken
+// relative to the match file
+fn resolve_image_path(image_path: &str, espanso_loc: &str, match_file: &Path) -> PathBuf {
+    if let Some(rest) = image_path.strip_prefix("$CONFIG") {
+        return Path::new(espanso_loc).join(rest.trim_start_matches(['/', '\\']));
+    }
+    let path = Path::new(image_path);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    match_file.parent().unwrap_or(Path::new("")).join(path)
+}
+
+fn trigger_input_id(index: usize) -> text_input::Id {
+    text_input::Id::new(format!("trigger-{}", index))
+}
+
+// Characters allowed while typing a file name
+fn has_valid_file_name_chars(file_name: &str) -> bool {
     let pattern = Regex::new(r"^[\w\-. ]+$").unwrap();
     pattern.is_match(file_name)
 }
 
-fn open_link(url: &str) {
+// A name that stays inside match/ and is usable on every platform: no
+// separators, no leading dot (hidden files, ..), no trailing dot or space, and
+// none of the names Windows reserves for devices
+fn is_valid_file_name(file_name: &str) -> bool {
+    const RESERVED_NAMES: [&str; 22] = [
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    if !has_valid_file_name_chars(file_name)
+        || file_name.starts_with('.')
+        || file_name.ends_with('.')
+        || file_name.ends_with(' ')
+    {
+        return false;
+    }
+    // Windows ignores anything after the first dot when checking reserved names
+    let device_name = file_name.split('.').next().unwrap_or_default().trim_end();
+    !RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device_name))
+}
+
+// Uses the browser command from Settings when there is one, with %u
+// replaced by the link (or the link added at the end)
+fn open_link(url: &str, browser_command: &str) {
+    let mut parts = browser_command.split_whitespace();
+    if let Some(program) = parts.next() {
+        let mut args: Vec<String> = parts.map(|part| part.replace("%u", url)).collect();
+        if !browser_command.contains("%u") {
+            args.push(url.to_string());
+        }
+        match std::process::Command::new(program).args(args).spawn() {
+            Ok(_) => return,
+            Err(err) => eprintln!("Failed to run browser command, using the default: {}", err),
+        }
+    }
     if let Err(err) = webbrowser::open(url) {
         eprintln!("Failed to open link: {}", err);
     }
 }
 
+// Triggers containing query, ignoring case, by the enabled match file they're in.
+// Files without one are left out.
+fn search_triggers(
+    espanso_loc: &str,
+    stems: &[String],
+    query: &str,
+) -> BTreeMap<String, Vec<String>> {
+    let query = query.to_lowercase();
+    let mut results = BTreeMap::new();
+    for stem in stems {
+        let Ok((yaml, _)) = read_yaml_text(&match_file_path(espanso_loc, stem, false)) else {
+            continue;
+        };
+        let Ok(file) = EspansoYaml::parse(&yaml, true) else {
+            continue;
+        };
+        // Preserved matches may list several triggers
+        let preserved = file.preserved_matches.iter().flat_map(|(_, value)| {
+            value
+                .get("trigger")
+                .and_then(|value| value.as_str())
+                .into_iter()
+                .chain(
+                    value
+                        .get("triggers")
+                        .and_then(|value| value.as_sequence())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|value| value.as_str()),
+                )
+                .map(String::from)
+                .collect::<Vec<String>>()
+        });
+        let found: Vec<String> = file
+            .matches
+            .iter()
+            .map(|pair| pair.trigger.clone())
+            .chain(preserved)
+            .filter(|trigger| trigger.to_lowercase().contains(&query))
+            .collect();
+        if !found.is_empty() {
+            results.insert(stem.clone(), found);
+        }
+    }
+    results
+}
+
+// The enabled match files with a match that uses trigger
+fn files_with_trigger(espanso_loc: &str, stems: &[String], trigger: &str) -> Vec<String> {
+    let trigger = trigger.trim();
+    if trigger.is_empty() || trigger.eq_ignore_ascii_case("off") {
+        return Vec::new();
+    }
+    stems
+        .iter()
+        .filter(|stem| {
+            let Ok((yaml, _)) = read_yaml_text(&match_file_path(espanso_loc, stem, false)) else {
+                return false;
+            };
+            let Ok(file) = EspansoYaml::parse(&yaml, true) else {
+                return false;
+            };
+            // Preserved matches may list several triggers
+            file.matches.iter().any(|pair| pair.trigger == trigger)
+                || file.preserved_matches.iter().any(|(_, value)| {
+                    value.get("trigger").and_then(|value| value.as_str()) == Some(trigger)
+                        || value
+                            .get("triggers")
+                            .and_then(|value| value.as_sequence())
+                            .is_some_and(|triggers| {
+                                triggers.iter().any(|value| value.as_str() == Some(trigger))
+                            })
+                })
+        })
+        .cloned()
+        .collect()
+}
+
+// True when the toggle key and both ways of opening search are all off
+fn config_disables_ui(config: &ParsedConfig) -> bool {
+    let is_off = |value: &str| value.trim().is_empty() || value.trim().eq_ignore_ascii_case("off");
+    let toggle_off = config.toggle_key.as_deref().is_none_or(is_off);
+    // espanso defaults to ALT+SPACE for search and no search trigger
+    let shortcut_off = config.search_shortcut.as_deref().is_some_and(is_off);
+    let trigger_off = config.search_trigger.as_deref().is_none_or(is_off);
+    toggle_off && shortcut_off && trigger_off
+}
+
+// Only the layout key is edited; the rest of the mapping stays as loaded
+fn set_keyboard_layout(config: &mut ParsedConfig, layout: String) {
+    let mut keyboard_layout = config.keyboard_layout.take().unwrap_or_default();
+    if layout.is_empty() {
+        keyboard_layout.remove("layout");
+    } else {
+        keyboard_layout.insert("layout".into(), layout.into());
+    }
+    config.keyboard_layout = (!keyboard_layout.is_empty()).then_some(keyboard_layout);
+}
+
+fn normalized_config(config: &ParsedConfig) -> ParsedConfig {
+    ParsedConfig {
+        backend: config_selection(&BACKEND_OPTIONS, &config.backend),
+        toggle_key: config_selection(&TOGGLE_KEY_OPTIONS, &config.toggle_key),
+        ..config.clone()
+    }
+}
+
+fn canonical_option(known: &[&str], value: &Option<String>) -> Option<String> {
+    let value = value.as_ref().filter(|value| !value.is_empty())?;
+    Some(find_known_option(known, value).unwrap_or_else(|| value.clone()))
+}
+
+// espanso's defaults, as the Config screen shows them for unset options
+fn espanso_default_config() -> ParsedConfig {
+    ParsedConfig {
+        backend: Some(BACKEND_OPTIONS[0].to_string()),
+        enable: Some(true),
+        toggle_key: Some(TOGGLE_KEY_OPTIONS[0].to_string()),
+        inject_delay: Some(0),
+        key_delay: Some(0),
+        clipboard_threshold: Some(100),
+        pre_paste_delay: Some(300),
+        disable_x11_fast_inject: Some(false),
+        paste_shortcut_event_delay: Some(10),
+        auto_restart: Some(true),
+        preserve_clipboard: Some(true),
+        restore_clipboard_delay: Some(300),
+        evdev_modifier_delay: Some(10),
+        word_separators: Some(get_default_word_separators()),
+        backspace_limit: Some(5),
+        apply_patch: Some(true),
+        undo_backspace: Some(true),
+        show_notifications: Some(true),
+        show_icon: Some(true),
+        x11_use_xclip_backend: Some(false),
+        win32_exclude_orphan_events: Some(true),
+        win32_keyboard_layout_cache_interval: Some(2000),
+        ..Default::default()
+    }
+}
+
+// Options the file didn't set and the user didn't change are left unset
+// rather than pinned to espanso's default, e.g. after applying a preset
+fn omit_untouched_defaults(
+    config: &mut ParsedConfig,
+    original: &ParsedConfig,
+    touched: &BTreeSet<&'static str>,
+) {
+    let defaults = espanso_default_config();
+    macro_rules! omit {
+        ($($field:ident),*) => {
+            $(
+                if !touched.contains(stringify!($field))
+                    && original.$field.is_none()
+                    && config.$field == defaults.$field
+                {
+                    config.$field = None;
+                }
+            )*
+        };
+    }
+    omit!(
+        backend,
+        enable,
+        toggle_key,
+        inject_delay,
+        key_delay,
+        clipboard_threshold,
+        pre_paste_delay,
+        disable_x11_fast_inject,
+        paste_shortcut_event_delay,
+        auto_restart,
+        preserve_clipboard,
+        restore_clipboard_delay,
+        evdev_modifier_delay,
+        word_separators,
+        backspace_limit,
+        apply_patch,
+        undo_backspace,
+        show_notifications,
+        show_icon,
+        x11_use_xclip_backend,
+        win32_exclude_orphan_events,
+        win32_keyboard_layout_cache_interval
+    );
+}
+
+fn find_known_option(known: &[&str], value: &str) -> Option<String> {
+    known
+        .iter()
+        .find(|option| option.eq_ignore_ascii_case(value))
+        .map(|option| option.to_string())
+}
+
+// Known options plus the current value if the GUI doesn't recognize it, so a
+// value written by hand or by a newer espanso isn't hidden or reset
+fn config_options(known: &[&str], current: &Option<String>) -> Vec<String> {
+    let mut options: Vec<String> = known.iter().map(|option| option.to_string()).collect();
+    if let Some(value) = current {
+        if !value.is_empty() && find_known_option(known, value).is_none() {
+            options.push(value.clone());
+        }
+    }
+    options
+}
+
+// A Config screen row label with a button to that option's entry in the docs
+fn config_label<'a>(label: &'a str, option: &str) -> Element<'a, Message> {
+    row![
+        text(label).size(20),
+        Tooltip::new(
+            button(text(icon_to_char(Nerd::InformationOutline)).font(NERD_FONT))
+                .on_press(Message::LaunchURL(config_doc_url(option)))
+                .style(button::text),
+            "Open the espanso docs for this option",
+            tooltip::Position::Right,
+        ),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center)
+    .width(300)
+    .into()
+}
+
+// The options reference anchors each option by its config key
+fn config_doc_url(option: &str) -> String {
+    format!("https://espanso.org/docs/configuration/options/#{}", option)
+}
+
+fn config_selection(known: &[&str], current: &Option<String>) -> Option<String> {
+    match current {
+        Some(value) if !value.is_empty() => {
+            find_known_option(known, value).or_else(|| Some(value.clone()))
+        }
+        _ => Some(known[0].to_string()),
+    }
+}
+
+fn unrecognized_option_warning<'a>(
+    known: &[&str],
+    current: &Option<String>,
+) -> Option<Element<'a, Message>> {
+    match current {
+        Some(value) if !value.is_empty() && find_known_option(known, value).is_none() => {
+            Some(text("Not a value espansoGUI recognizes; it will be kept as-is").into())
+        }
+        _ => None,
+    }
+}
+
+// Reads "trigger,replace" lines into pairs and lines with only a trigger into
+// triggers. A single line of several commas is read as a list of triggers.
+fn parse_match_list(text: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if let [line] = lines.as_slice() {
+        let triggers: Vec<&str> = line.split(',').map(str::trim).collect();
+        if triggers.len() > 2 || triggers.last().is_some_and(|trigger| trigger.is_empty()) {
+            return (
+                Vec::new(),
+                triggers
+                    .into_iter()
+                    .filter(|trigger| !trigger.is_empty())
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+    }
+    let mut pairs = Vec::new();
+    let mut triggers = Vec::new();
+    for line in lines {
+        match line.split_once(',') {
+            Some((trigger, replace)) if !replace.trim().is_empty() => {
+                pairs.push((trigger.trim().to_string(), replace.trim().to_string()))
+            }
+            Some((trigger, _)) => triggers.push(trigger.trim().to_string()),
+            None => triggers.push(line.to_string()),
+        }
+    }
+    (pairs, triggers)
+}
+
+fn pasted_list_toast(added: usize, skipped: usize) -> String {
+    if skipped > 0 {
+        format!(
+            "Added {} match(es), skipped {} already in the file",
+            added, skipped
+        )
+    } else {
+        format!("Added {} match(es)", added)
+    }
+}
+
+// The longest text every one of texts contains, trimmed of surrounding whitespace
+fn longest_common_substring(texts: &[&str]) -> String {
+    let Some(shortest) = texts.iter().min_by_key(|text| text.len()) else {
+        return String::new();
+    };
+    let boundaries: Vec<usize> = shortest
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([shortest.len()])
+        .collect();
+    let is_common = |candidate: &str| texts.iter().all(|text| text.contains(candidate));
+    let mut best = "";
+    for (start_index, start) in boundaries.iter().enumerate() {
+        // Shortening a common substring keeps it common, so the longest one
+        // from this start can be binary searched
+        let ends = &boundaries[start_index + 1..];
+        let common_ends = ends.partition_point(|end| is_common(&shortest[*start..*end]));
+        if let Some(end) = common_ends.checked_sub(1).map(|i| ends[i]) {
+            if end - start > best.len() {
+                best = &shortest[*start..end];
+            }
+        }
+    }
+    best.trim().to_string()
+}
+
+// A variable name suggested from the text it holds, e.g. "Best regards" -> best_regards
+fn var_name_for(text: &str) -> String {
+    let name: String = text
+        .split(|character: char| !character.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(3)
+        .collect::<Vec<&str>>()
+        .join("_")
+        .to_lowercase();
+    if name.is_empty() {
+        "shared".to_string()
+    } else if name.starts_with(|character: char| character.is_ascii_digit()) {
+        format!("shared_{}", name)
+    } else {
+        name
+    }
+}
+
+// Called from view, so no regex to build every frame
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// espanso moves the cursor to the first $|$ and leaves any others in the text
+fn extra_cursor_hints(replace: &str) -> usize {
+    replace.matches("$|$").count().saturating_sub(1)
+}
+
+fn keep_first_cursor_hint(replace: &str) -> String {
+    match replace.find("$|$") {
+        Some(start) => {
+            let (kept, rest) = replace.split_at(start + "$|$".len());
+            format!("{}{}", kept, rest.replace("$|$", ""))
+        }
+        None => replace.to_string(),
+    }
+}
+
+// Usually a typo, since espanso matches whitespace in a trigger literally
+fn has_untrimmed_trigger(a_match: &YamlPairs) -> bool {
+    let trimmed = a_match.trigger.trim();
+    !trimmed.is_empty() && trimmed != a_match.trigger
+}
+
+// The first line of text, cut to max_chars, for one-line previews
+fn preview_line(text: &str, max_chars: usize) -> String {
+    let first_line = text.lines().next().unwrap_or_default();
+    if first_line.chars().count() > max_chars {
+        format!(
+            "{}…",
+            first_line.chars().take(max_chars).collect::<String>()
+        )
+    } else if text.lines().nth(1).is_some() {
+        format!("{}…", first_line)
+    } else {
+        first_line.to_string()
+    }
+}
+
+// Control characters are shown escaped, e.g. "\t" or "\u0016"
+fn word_separators_text(config: &ParsedConfig) -> String {
+    match &config.word_separators {
+        Some(separators) => serde_json::to_string(separators).unwrap_or_default(),
+        None => serde_json::to_string(&get_default_word_separators()).unwrap_or_default(),
+    }
+}
+
+// Reads a list of quoted separators, e.g. [" ", "\t", "\u{16}"]. Accepts JSON
+// escapes and the Rust-style ones older versions showed, in single or double
+// quotes, with or without the brackets.
+fn parse_word_separators(text: &str) -> Result<Vec<String>, String> {
+    let trimmed = text.trim();
+    let inner = trimmed
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap_or(trimmed);
+    let mut separators = Vec::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        let quote = match c {
+            '"' | '\'' => c,
+            ',' => continue,
+            c if c.is_whitespace() => continue,
+            c => return Err(format!("Expected a quoted separator but found '{}'.", c)),
+        };
+        let mut separator = String::new();
+        loop {
+            match chars.next() {
+                None => return Err("A separator is missing its closing quote.".to_string()),
+                Some(c) if c == quote => break,
+                Some('\\') => separator.push(parse_escape(&mut chars)?),
+                Some(c) => separator.push(c),
+            }
+        }
+        if separator.is_empty() {
+            return Err("Separators can't be empty.".to_string());
+        }
+        separators.push(separator);
+    }
+    Ok(separators)
+}
+
+fn parse_escape(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<char, String> {
+    let hex_char = |digits: &str| {
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("\"{}\" isn't a valid character code.", digits))
+    };
+    match chars.next() {
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+        Some('b') => Ok('\u{8}'),
+        Some('f') => Ok('\u{c}'),
+        Some(c @ ('\\' | '"' | '\'' | '/')) => Ok(c),
+        Some('x') => hex_char(&chars.by_ref().take(2).collect::<String>()),
+        Some('u') if chars.peek() == Some(&'{') => {
+            chars.next();
+            let digits: String = chars.by_ref().take_while(|c| *c != '}').collect();
+            hex_char(&digits)
+        }
+        Some('u') => hex_char(&chars.by_ref().take(4).collect::<String>()),
+        Some(c) => Err(format!("\"\\{}\" isn't a recognized escape.", c)),
+        None => Err("A separator ends with an unfinished escape.".to_string()),
+    }
+}
+
+fn builtin_presets() -> Vec<(&'static str, ParsedConfig)> {
+    vec![
+        (
+            "Fast injection",
+            ParsedConfig {
+                backend: Some("Inject".to_string()),
+                inject_delay: Some(0),
+                key_delay: Some(0),
+                ..Default::default()
+            },
+        ),
+        (
+            "Safe clipboard",
+            ParsedConfig {
+                backend: Some("Clipboard".to_string()),
+                preserve_clipboard: Some(true),
+                restore_clipboard_delay: Some(300),
+                ..Default::default()
+            },
+        ),
+    ]
+}
+
+// Presets carry behaviour only; which apps and files a config covers stays
+// with the config being edited
+fn without_file_options(config: ParsedConfig) -> ParsedConfig {
+    ParsedConfig {
+        label: None,
+        includes: None,
+        excludes: None,
+        extra_includes: None,
+        extra_excludes: None,
+        use_standard_includes: None,
+        filter_title: None,
+        filter_class: None,
+        filter_exec: None,
+        filter_os: None,
+        ..config
+    }
+}
+
+fn apply_preset(config: &ParsedConfig, preset: ParsedConfig) -> ParsedConfig {
+    ParsedConfig {
+        label: config.label.clone(),
+        includes: config.includes.clone(),
+        excludes: config.excludes.clone(),
+        extra_includes: config.extra_includes.clone(),
+        extra_excludes: config.extra_excludes.clone(),
+        use_standard_includes: config.use_standard_includes,
+        filter_title: config.filter_title.clone(),
+        filter_class: config.filter_class.clone(),
+        filter_exec: config.filter_exec.clone(),
+        filter_os: config.filter_os.clone(),
+        flow_sequences: config.flow_sequences.clone(),
+        ..preset
+    }
+}
+
 fn get_default_word_separators() -> Vec<String> {
     vec![
         " ".to_string(),
@@ -1611,3 +7565,182 @@ where
     ]
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_separators_round_trip() {
+        let separators: Vec<String> = [" ", "\t", "\r", "\n", "\u{16}"]
+            .iter()
+            .map(|separator| separator.to_string())
+            .collect();
+        let config = ParsedConfig {
+            word_separators: Some(separators.clone()),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_word_separators(&word_separators_text(&config)),
+            Ok(separators)
+        );
+    }
+
+    #[test]
+    fn word_separators_accept_rust_style_escapes() {
+        assert_eq!(
+            parse_word_separators(r#"[' ', '\t', '\r', '\n', '\u{16}', "\x16"]"#),
+            Ok([" ", "\t", "\r", "\n", "\u{16}", "\u{16}"]
+                .iter()
+                .map(|separator| separator.to_string())
+                .collect())
+        );
+    }
+
+    #[test]
+    fn parse_escape_reads_each_separator() {
+        for (escape, expected) in [
+            ("t", '\t'),
+            ("r", '\r'),
+            ("n", '\n'),
+            ("u0016", '\u{16}'),
+            ("u{16}", '\u{16}'),
+            ("x16", '\u{16}'),
+            ("u0020", ' '),
+        ] {
+            assert_eq!(parse_escape(&mut escape.chars().peekable()), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn word_separators_reject_malformed_lists() {
+        assert!(parse_word_separators(r#"[" ", "\t]"#).is_err());
+        assert!(parse_word_separators(r#"[" ", ""]"#).is_err());
+        assert!(parse_word_separators(r#"[" ", x]"#).is_err());
+    }
+
+    #[test]
+    fn unset_options_match_their_defaults() {
+        let unset = ParsedConfig::default();
+        let explicit = ParsedConfig {
+            backend: Some("auto".to_string()),
+            toggle_key: Some("off".to_string()),
+            ..Default::default()
+        };
+        assert!(normalized_config(&unset) == normalized_config(&explicit));
+        let recased = ParsedConfig {
+            backend: Some("CLIPBOARD".to_string()),
+            toggle_key: Some("alt".to_string()),
+            ..Default::default()
+        };
+        let canonical = ParsedConfig {
+            backend: Some("Clipboard".to_string()),
+            toggle_key: Some("ALT".to_string()),
+            ..Default::default()
+        };
+        assert!(normalized_config(&recased) == normalized_config(&canonical));
+    }
+
+    #[test]
+    fn edited_options_are_changes() {
+        let unset = ParsedConfig::default();
+        let edited = ParsedConfig {
+            backend: Some("Inject".to_string()),
+            ..Default::default()
+        };
+        assert!(normalized_config(&unset) != normalized_config(&edited));
+        let edited = ParsedConfig {
+            toggle_key: Some("CTRL".to_string()),
+            ..Default::default()
+        };
+        assert!(normalized_config(&unset) != normalized_config(&edited));
+    }
+
+    #[test]
+    fn normalizes_windows_paths() {
+        assert_eq!(
+            normalize_dir_path(r"C:/Users\me//AppData\Roaming/espanso", true),
+            r"C:\Users\me\AppData\Roaming\espanso"
+        );
+        assert_eq!(normalize_dir_path(r"C:\espanso\", true), r"C:\espanso");
+        assert_eq!(normalize_dir_path("C:/espanso//", true), r"C:\espanso");
+        assert_eq!(
+            normalize_dir_path(r"\\server\share\espanso\", true),
+            r"\\server\share\espanso"
+        );
+        assert_eq!(
+            normalize_dir_path("//server/share", true),
+            r"\\server\share"
+        );
+        assert_eq!(normalize_dir_path("C:", true), r"C:\");
+        assert_eq!(normalize_dir_path(r"C:\", true), r"C:\");
+    }
+
+    #[test]
+    fn normalizes_unix_paths() {
+        assert_eq!(
+            normalize_dir_path("/home/me//espanso/", false),
+            "/home/me/espanso"
+        );
+        assert_eq!(normalize_dir_path("/", false), "/");
+        assert_eq!(normalize_dir_path("//", false), "/");
+        assert_eq!(
+            normalize_dir_path(r"/home/me\espanso", false),
+            r"/home/me\espanso"
+        );
+    }
+
+    #[test]
+    fn file_names_stay_in_the_match_folder() {
+        for name in [
+            "../evil", "a/b", r"a\b", ".hidden", "name.", "name ", "CON", "con.yml", "LPT1.txt", "",
+        ] {
+            assert!(!is_valid_file_name(name), "{:?} should be rejected", name);
+        }
+        for name in ["my-file_1", "base", "work notes", "v1.2", "console"] {
+            assert!(is_valid_file_name(name), "{:?} should be allowed", name);
+        }
+    }
+
+    #[test]
+    fn keyboard_layout_edit_keeps_other_keys() {
+        let path = env::temp_dir().join("espanso-gui-keyboard-layout-test.yml");
+        std::fs::write(
+            &path,
+            "keyboard_layout:\n  layout: us\n  variant: dvorak\n  options: caps:escape\n",
+        )
+        .unwrap();
+        let mut config = ParsedConfig::load(&path).unwrap();
+        remove_file(&path).unwrap();
+
+        set_keyboard_layout(&mut config, "de".to_string());
+        let saved: serde_yaml::Value =
+            serde_yaml::from_str(&config_yaml(&config).unwrap()).unwrap();
+        let keyboard_layout = &saved["keyboard_layout"];
+        assert_eq!(keyboard_layout["layout"].as_str(), Some("de"));
+        assert_eq!(keyboard_layout["variant"].as_str(), Some("dvorak"));
+        assert_eq!(keyboard_layout["options"].as_str(), Some("caps:escape"));
+    }
+
+    #[test]
+    fn untrimmed_notes_dont_block_saving() {
+        let mut file = EspansoYaml::default();
+        file.matches.push(YamlPairs {
+            trigger: ":sig".to_string(),
+            replace: "Best,\nAlex".to_string(),
+            note: "  signature ".to_string(),
+            ..YamlPairs::default()
+        });
+        for block_replace in [true, false] {
+            assert_eq!(
+                first_changed_by_round_trip(&file, block_replace, false),
+                None
+            );
+        }
+
+        // Empty matches are dropped on reading unless they're kept
+        file.matches[0].trigger.clear();
+        file.matches[0].replace.clear();
+        assert_eq!(first_changed_by_round_trip(&file, true, false), Some(0));
+    }
+}
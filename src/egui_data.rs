@@ -14,9 +14,100 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::parse_config::ParsedConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct EGUIData {
     pub espanso_dir: String,
+    #[serde(default)]
+    pub recent_dirs: Vec<String>,
+    // Applied in main() before the window is created
+    #[serde(default)]
+    pub software_renderer: bool,
+    #[serde(default)]
+    pub present_mode: Option<String>,
+    #[serde(default)]
+    pub antialiasing: bool,
+    // Load matches with an empty trigger or replace as editable rows
+    #[serde(default)]
+    pub preserve_empty_matches: bool,
+    #[serde(default)]
+    pub compact_view: bool,
+    // The view each file was left in, by its path under match/, overriding compact_view
+    #[serde(default)]
+    pub file_compact_views: BTreeMap<String, bool>,
+    #[serde(default)]
+    pub high_contrast: bool,
+    #[serde(default)]
+    pub browser_command: String,
+    // Hex color like "#2E9E6A" for buttons and highlights, empty for the theme's own
+    #[serde(default)]
+    pub accent_color: String,
+    // Show the multi-line editor even for one-line replacements
+    #[serde(default)]
+    pub always_multiline_replace: bool,
+    // Ask before saving replacements with more than one $|$
+    #[serde(default)]
+    pub warn_cursor_hints: bool,
+    // Write multi-line replacements as | blocks instead of escaped strings
+    #[serde(default = "default_block_scalar_replace")]
+    pub block_scalar_replace: bool,
+    // Files with more matches than this get a warning in the nav
+    #[serde(default = "default_large_file_threshold")]
+    pub large_file_threshold: usize,
+    // Save the open file this many seconds after the last change
+    #[serde(default)]
+    pub auto_save: bool,
+    #[serde(default = "default_auto_save_delay")]
+    pub auto_save_delay: u64,
+    // Larger match files aren't opened
+    #[serde(default = "default_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    // Config snapshots the user saved by name
+    #[serde(default)]
+    pub config_presets: BTreeMap<String, ParsedConfig>,
+}
+
+impl Default for EGUIData {
+    fn default() -> Self {
+        EGUIData {
+            espanso_dir: String::new(),
+            recent_dirs: Vec::new(),
+            software_renderer: false,
+            present_mode: None,
+            antialiasing: false,
+            preserve_empty_matches: false,
+            compact_view: false,
+            file_compact_views: BTreeMap::new(),
+            high_contrast: false,
+            browser_command: String::new(),
+            accent_color: String::new(),
+            always_multiline_replace: false,
+            warn_cursor_hints: false,
+            block_scalar_replace: default_block_scalar_replace(),
+            large_file_threshold: default_large_file_threshold(),
+            max_file_size_mb: default_max_file_size_mb(),
+            auto_save: false,
+            auto_save_delay: default_auto_save_delay(),
+            config_presets: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_large_file_threshold() -> usize {
+    500
+}
+
+fn default_max_file_size_mb() -> u64 {
+    5
+}
+
+fn default_auto_save_delay() -> u64 {
+    3
+}
+
+fn default_block_scalar_replace() -> bool {
+    true
 }
@@ -14,17 +14,711 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeMap, ser::SerializeSeq, Deserialize, Serialize, Serializer};
+use serde_yaml::Value;
+use std::path::Path;
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+// Keys a match may contain and still be edited as a trigger/replace pair
+const EDITABLE_KEYS: [&str; 5] = ["trigger", "replace", "image_path", "word", "search_terms"];
+
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
 pub struct YamlPairs {
     #[serde(default)]
     pub trigger: String,
     #[serde(default)]
     pub replace: String,
+    // Image matches paste this image instead of replace text
+    #[serde(default)]
+    pub image_path: Option<String>,
+    // Only fire when the trigger is typed as a whole word
+    #[serde(default)]
+    pub word: bool,
+    // Extra words that find this match in espanso's search bar
+    #[serde(default)]
+    pub search_terms: Vec<String>,
+    // Written as a "# note:" comment above the match, not as a key
+    #[serde(skip)]
+    pub note: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+impl Serialize for YamlPairs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("trigger", &self.trigger)?;
+        // Image matches have no replace, but a blank text match keeps its empty one
+        if !self.replace.is_empty() || self.image_path.is_none() {
+            map.serialize_entry("replace", &self.replace)?;
+        }
+        if let Some(image_path) = &self.image_path {
+            map.serialize_entry("image_path", image_path)?;
+        }
+        if self.word {
+            map.serialize_entry("word", &self.word)?;
+        }
+        if !self.search_terms.is_empty() {
+            map.serialize_entry("search_terms", &self.search_terms)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
+#[serde(from = "RawEspansoYaml")]
 pub struct EspansoYaml {
+    pub imports: Vec<String>,
+    pub global_vars: Vec<Value>,
     pub matches: Vec<YamlPairs>,
+    // Matches the GUI can't edit (other match forms, half-written entries),
+    // kept with the number of editable matches that preceded them on load
+    pub preserved_matches: Vec<(usize, Value)>,
+    // Notes of preserved matches, by their index in preserved_matches
+    pub preserved_notes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawEspansoYaml {
+    #[serde(default)]
+    imports: Vec<String>,
+    #[serde(default)]
+    global_vars: Vec<Value>,
+    #[serde(default)]
+    matches: Vec<Value>,
+}
+
+impl From<RawEspansoYaml> for EspansoYaml {
+    fn from(raw: RawEspansoYaml) -> Self {
+        EspansoYaml::from_raw(raw, false, &[])
+    }
+}
+
+impl Serialize for EspansoYaml {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if !self.imports.is_empty() {
+            map.serialize_entry("imports", &self.imports)?;
+        }
+        if !self.global_vars.is_empty() {
+            map.serialize_entry("global_vars", &self.global_vars)?;
+        }
+        map.serialize_entry("matches", &MatchList(self))?;
+        map.end()
+    }
+}
+
+struct MatchList<'a>(&'a EspansoYaml);
+
+impl Serialize for MatchList<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let file = self.0;
+        let mut seq =
+            serializer.serialize_seq(Some(file.matches.len() + file.preserved_matches.len()))?;
+        let mut preserved = file.preserved_matches.iter().peekable();
+        for (i, pair) in file.matches.iter().enumerate() {
+            while let Some((_, value)) = preserved.next_if(|(position, _)| *position <= i) {
+                seq.serialize_element(value)?;
+            }
+            seq.serialize_element(pair)?;
+        }
+        for (_, value) in preserved {
+            seq.serialize_element(value)?;
+        }
+        seq.end()
+    }
+}
+
+impl EspansoYaml {
+    // With keep_empty_matches, matches missing a trigger or replace are loaded
+    // as editable rows instead of being preserved as-is
+    pub fn parse(yaml: &str, keep_empty_matches: bool) -> Result<Self, serde_yaml::Error> {
+        let raw: RawEspansoYaml = serde_yaml::from_str(yaml)?;
+        let mut notes = match_notes(yaml);
+        // Flow style lists and the like can't be lined up with their comments
+        if notes.len() != raw.matches.len() {
+            notes.clear();
+        }
+        Ok(EspansoYaml::from_raw(raw, keep_empty_matches, &notes))
+    }
+
+    fn from_raw(raw: RawEspansoYaml, keep_empty_matches: bool, notes: &[String]) -> Self {
+        let mut espanso_yaml = EspansoYaml {
+            imports: raw.imports,
+            global_vars: raw.global_vars,
+            ..Default::default()
+        };
+        for (i, value) in raw.matches.into_iter().enumerate() {
+            let note = notes.get(i).cloned().unwrap_or_default();
+            match as_editable_pair(&value, keep_empty_matches) {
+                Some(pair) => espanso_yaml.matches.push(YamlPairs { note, ..pair }),
+                None => {
+                    espanso_yaml
+                        .preserved_matches
+                        .push((espanso_yaml.matches.len(), value));
+                    espanso_yaml.preserved_notes.push(note);
+                }
+            }
+        }
+        espanso_yaml
+    }
+
+    // Serializes the file with each match's note as a comment above it
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        self.to_yaml_with(true)
+    }
+
+    // Without block_replace, multi-line replacements are written as one
+    // double-quoted line with \n escapes instead of a | block
+    pub fn to_yaml_with(&self, block_replace: bool) -> Result<String, serde_yaml::Error> {
+        let serialized = serde_yaml::to_string(self)?;
+        // Same order MatchList writes the matches in, with the replace of
+        // each editable match
+        let preserved_note = |j: usize| self.preserved_notes.get(j).map_or("", String::as_str);
+        let mut items = Vec::new();
+        let mut preserved = self.preserved_matches.iter().enumerate().peekable();
+        for (i, pair) in self.matches.iter().enumerate() {
+            while let Some((j, _)) = preserved.next_if(|(_, (position, _))| *position <= i) {
+                items.push((preserved_note(j), None));
+            }
+            items.push((pair.note.as_str(), Some(pair.replace.as_str())));
+        }
+        items.extend(preserved.map(|(j, _)| (preserved_note(j), None)));
+
+        let mut yaml = String::new();
+        let mut in_matches = false;
+        let mut in_block = false;
+        let mut replace = None;
+        let mut items = items.into_iter();
+        for line in serialized.lines() {
+            if in_block {
+                // The block's lines are indented past the match's keys
+                if line.is_empty() || line.starts_with("   ") {
+                    continue;
+                }
+                in_block = false;
+            }
+            if line == "matches:" {
+                in_matches = true;
+            } else if in_matches && (line == "-" || line.starts_with("- ")) {
+                let (note, item_replace) = items.next().unwrap_or_default();
+                replace = item_replace;
+                let note = note.trim();
+                if !note.is_empty() {
+                    yaml.push_str(&format!("# note: {}\n", note.replace('\n', " ")));
+                }
+            } else if !block_replace && line.starts_with("  replace: |") {
+                if let Some(text) = replace {
+                    // A JSON string is also a valid double-quoted YAML scalar
+                    yaml.push_str(&format!(
+                        "  replace: {}\n",
+                        serde_json::to_string(text).unwrap_or_default()
+                    ));
+                    in_block = true;
+                    continue;
+                }
+            }
+            yaml.push_str(line);
+            yaml.push('\n');
+        }
+        Ok(yaml)
+    }
+
+    // Writes the file back out and reads it again, to tell whether saving
+    // would change or drop anything from yaml, the text it was parsed from
+    pub fn lossy_reason(&self, yaml: &str) -> Option<&'static str> {
+        let rewritten = self.to_yaml().ok()?;
+        let mut original: Value = serde_yaml::from_str(yaml).ok()?;
+        let mut reread: Value = serde_yaml::from_str(&rewritten).ok()?;
+        // Keys left at their defaults aren't written, which changes nothing
+        let without_defaults = |file: &mut Value| {
+            if let Some(matches) = file.get_mut("matches").and_then(Value::as_sequence_mut) {
+                for a_match in matches.iter_mut().filter_map(Value::as_mapping_mut) {
+                    a_match.retain(|key, value| {
+                        !(key == "word" && value == &Value::Bool(false)
+                            || key == "search_terms"
+                                && value.as_sequence().is_some_and(Vec::is_empty))
+                    });
+                }
+            }
+        };
+        without_defaults(&mut original);
+        without_defaults(&mut reread);
+        // Empty files and ones with only comments read as null
+        let empty =
+            original.is_null() && self.matches.is_empty() && self.preserved_matches.is_empty();
+        if !empty && original != reread {
+            return Some(
+                "has keys or values the editor can't show, which saving may change or drop.",
+            );
+        }
+        let comments = |text: &str| {
+            text.lines()
+                .filter(|line| line.trim_start().starts_with('#'))
+                .count()
+        };
+        if comments(yaml) > comments(&rewritten) {
+            return Some("has comments, which saving will remove. Notes above matches are kept.");
+        }
+        None
+    }
+
+    // Appends other's matches, skipping any whose trigger this file already
+    // uses. Returns how many were added and how many were skipped.
+    pub fn append(&mut self, other: EspansoYaml) -> (usize, usize) {
+        let mut triggers: Vec<String> = self
+            .matches
+            .iter()
+            .map(|pair| pair.trigger.clone())
+            .chain(
+                self.preserved_matches
+                    .iter()
+                    .filter_map(|(_, value)| value_trigger(value)),
+            )
+            .collect();
+        let (mut added, mut skipped) = (0, 0);
+        for (_, value) in other.preserved_matches {
+            match value_trigger(&value) {
+                Some(trigger) if triggers.contains(&trigger) => skipped += 1,
+                trigger => {
+                    triggers.extend(trigger);
+                    self.preserved_matches.push((self.matches.len(), value));
+                    added += 1;
+                }
+            }
+        }
+        for pair in other.matches {
+            if triggers.contains(&pair.trigger) {
+                skipped += 1;
+            } else {
+                triggers.push(pair.trigger.clone());
+                self.matches.push(pair);
+                added += 1;
+            }
+        }
+        (added, skipped)
+    }
+
+    // A preserved match stays after the editable match it followed when
+    // matches are inserted, removed or moved
+    pub fn insert_match(&mut self, index: usize, pair: YamlPairs) {
+        self.matches.insert(index, pair);
+        for (position, _) in &mut self.preserved_matches {
+            if *position > index {
+                *position += 1;
+            }
+        }
+    }
+
+    pub fn remove_match(&mut self, index: usize) -> YamlPairs {
+        for (position, _) in &mut self.preserved_matches {
+            if *position > index {
+                *position -= 1;
+            }
+        }
+        self.matches.remove(index)
+    }
+
+    pub fn move_match(&mut self, from: usize, to: usize) {
+        let pair = self.remove_match(from);
+        self.insert_match(to, pair);
+    }
+
+    // Rearranges the matches given their current indices in the new order.
+    // A preserved match moves with the editable match that followed it, and
+    // ones at the end stay there.
+    pub fn reorder_matches(&mut self, order: &[usize]) {
+        let len = self.matches.len();
+        let mut new_index = vec![len; len + 1];
+        for (k, i) in order.iter().enumerate() {
+            new_index[*i] = k;
+        }
+        let mut matches: Vec<Option<YamlPairs>> = std::mem::take(&mut self.matches)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.matches = order.iter().filter_map(|i| matches[*i].take()).collect();
+
+        // MatchList expects them sorted by position, so reorder the notes too
+        self.preserved_notes
+            .resize(self.preserved_matches.len(), String::new());
+        let mut preserved: Vec<((usize, Value), String)> =
+            std::mem::take(&mut self.preserved_matches)
+                .into_iter()
+                .zip(std::mem::take(&mut self.preserved_notes))
+                .map(|((position, value), note)| ((new_index[position.min(len)], value), note))
+                .collect();
+        preserved.sort_by_key(|((position, _), _)| *position);
+        (self.preserved_matches, self.preserved_notes) = preserved.into_iter().unzip();
+    }
+
+    // Parses YAML shared outside of a file: a whole match file, a list of
+    // matches, or a single match
+    pub fn from_snippet(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        let value: Value = serde_yaml::from_str(yaml)?;
+        let matches = match value {
+            Value::Mapping(ref mapping) if mapping.contains_key("matches") => {
+                return serde_yaml::from_value(value)
+            }
+            Value::Sequence(sequence) => sequence,
+            Value::Mapping(_) => vec![value],
+            _ => return Err(serde::de::Error::custom("expected espanso matches")),
+        };
+        if !matches.iter().all(is_match) {
+            return Err(serde::de::Error::custom("expected espanso matches"));
+        }
+        Ok(RawEspansoYaml {
+            imports: Vec::new(),
+            global_vars: Vec::new(),
+            matches,
+        }
+        .into())
+    }
+
+    pub fn to_snippet(matches: &[YamlPairs]) -> String {
+        serde_yaml::to_string(&EspansoYaml {
+            matches: matches.to_vec(),
+            ..Default::default()
+        })
+        .unwrap_or_default()
+    }
+}
+
+// Reads a YAML file as UTF-8 text. Files saved by Windows editors with a byte
+// order mark, as UTF-16 or in a legacy code page are converted, along with a
+// warning for the user since saving writes them back as plain UTF-8.
+pub fn read_yaml_text(path: &Path) -> std::io::Result<(String, Option<String>)> {
+    Ok(decode_yaml_bytes(&std::fs::read(path)?))
+}
+
+fn decode_yaml_bytes(bytes: &[u8]) -> (String, Option<String>) {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        let (text, warning) = decode_yaml_bytes(rest);
+        let warning = warning.or_else(|| {
+            Some(
+                "starts with a byte order mark, which will be removed when it's saved.".to_string(),
+            )
+        });
+        return (text, warning);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return (
+            utf16(rest, u16::from_le_bytes),
+            Some("is encoded as UTF-16 and will be saved as UTF-8.".to_string()),
+        );
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return (
+            utf16(rest, u16::from_be_bytes),
+            Some("is encoded as UTF-16 and will be saved as UTF-8.".to_string()),
+        );
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), None),
+        // Most likely a Windows code page; Latin-1 keeps every byte readable
+        Err(_) => (
+            bytes.iter().map(|byte| *byte as char).collect(),
+            Some(
+                "isn't valid UTF-8, so it was read as Latin-1. Check any accented characters before saving, as it will be saved as UTF-8."
+                    .to_string(),
+            ),
+        ),
+    }
+}
+
+// The "# note:" comment lines directly above each item of the top level
+// matches list, empty for items without one
+fn match_notes(yaml: &str) -> Vec<String> {
+    let mut notes = Vec::new();
+    let mut pending: Vec<&str> = Vec::new();
+    let mut in_matches = false;
+    let mut item_indent = None;
+    for line in yaml.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if !in_matches {
+            in_matches = line.trim_end() == "matches:";
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            match comment.trim_start().strip_prefix("note:") {
+                Some(note) => pending.push(note.trim()),
+                None => pending.clear(),
+            }
+            continue;
+        }
+        if indent == 0 && !trimmed.is_empty() && !trimmed.starts_with('-') {
+            // The next top level key
+            break;
+        }
+        if (trimmed == "-" || trimmed.starts_with("- "))
+            && *item_indent.get_or_insert(indent) == indent
+        {
+            notes.push(pending.join(" "));
+        }
+        pending.clear();
+    }
+    notes
+}
+
+// Number of distinct [[field]] placeholders in a form match, or None if
+// the match isn't a form
+pub fn form_field_count(value: &Value) -> Option<usize> {
+    let form = value.get("form")?.as_str()?;
+    let mut fields: Vec<&str> = form
+        .split("[[")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("]]").map(|(field, _)| field.trim()))
+        .collect();
+    fields.sort();
+    fields.dedup();
+    Some(fields.len())
+}
+
+// Text the writer has to quote because, left bare, YAML would read it as a
+// list item, mapping, comment, number or other non-string value
+pub fn quoted_in_yaml(text: &str) -> bool {
+    !text.is_empty()
+        && serde_yaml::to_string(&Value::String(text.to_string()))
+            .is_ok_and(|yaml| yaml.starts_with(['\'', '"']))
+}
+
+fn value_trigger(value: &Value) -> Option<String> {
+    value
+        .get("trigger")?
+        .as_str()
+        .map(|trigger| trigger.to_string())
+}
+
+fn is_match(value: &Value) -> bool {
+    value.as_mapping().is_some_and(|mapping| {
+        ["trigger", "triggers", "regex"]
+            .iter()
+            .any(|key| mapping.contains_key(*key))
+    })
+}
+
+fn as_editable_pair(value: &Value, allow_empty: bool) -> Option<YamlPairs> {
+    let mapping = value.as_mapping()?;
+    let only_editable_keys = mapping
+        .keys()
+        .all(|key| key.as_str().is_some_and(|key| EDITABLE_KEYS.contains(&key)));
+    if !only_editable_keys {
+        return None;
+    }
+    let pair: YamlPairs = serde_yaml::from_value(value.clone()).ok()?;
+    // Exactly one of replace or image_path, unless blank rows are allowed
+    let has_image = pair
+        .image_path
+        .as_ref()
+        .is_some_and(|path| !path.is_empty());
+    let blank = pair.trigger.is_empty() || (pair.replace.is_empty() && !has_image);
+    if (!pair.replace.is_empty() && has_image) || (blank && !allow_empty) {
+        return None;
+    }
+    Some(pair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Editable matches around a regex match and a form, which are preserved
+    const MIXED: &str = r#"matches:
+  - trigger: ":a"
+    replace: "A"
+  # note: feet
+  - regex: "(?P<n>\\d+)ft"
+    replace: "{{n}} feet"
+  - trigger: ":b"
+    replace: "B"
+  - trigger: ":form"
+    form: "Hi [[name]]"
+  - trigger: ":c"
+    replace: "C"
+"#;
+
+    // Triggers (or regexes) in the order the file is written
+    fn written(file: &EspansoYaml) -> Vec<String> {
+        let value: Value = serde_yaml::from_str(&file.to_yaml().unwrap()).unwrap();
+        value["matches"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|a_match| {
+                let key = a_match.get("trigger").or_else(|| a_match.get("regex"));
+                key.and_then(Value::as_str).unwrap().to_string()
+            })
+            .collect()
+    }
+
+    fn pair(trigger: &str, replace: &str) -> YamlPairs {
+        YamlPairs {
+            trigger: trigger.to_string(),
+            replace: replace.to_string(),
+            ..YamlPairs::default()
+        }
+    }
+
+    #[test]
+    fn preserved_matches_follow_their_neighbour_through_a_sort() {
+        let mut file = EspansoYaml::parse(MIXED, false).unwrap();
+        file.reorder_matches(&[2, 1, 0]);
+        assert_eq!(written(&file), [":form", ":c", "(?P<n>\\d+)ft", ":b", ":a"]);
+        let yaml = file.to_yaml().unwrap();
+        assert!(yaml.contains("# note: feet\n- regex:"), "{}", yaml);
+        let reread = EspansoYaml::parse(&yaml, false).unwrap();
+        assert_eq!(reread.preserved_notes, ["", "feet"]);
+    }
+
+    #[test]
+    fn removing_and_moving_keep_preserved_matches_in_place() {
+        let mut file = EspansoYaml::parse(MIXED, false).unwrap();
+        assert_eq!(file.remove_match(1), pair(":b", "B"));
+        assert_eq!(written(&file), [":a", "(?P<n>\\d+)ft", ":form", ":c"]);
+
+        let mut file = EspansoYaml::parse(MIXED, false).unwrap();
+        file.move_match(0, 2);
+        assert_eq!(written(&file), ["(?P<n>\\d+)ft", ":b", ":form", ":c", ":a"]);
+        // Having followed nothing, the regex match stays first
+        file.move_match(2, 0);
+        assert_eq!(written(&file), ["(?P<n>\\d+)ft", ":a", ":b", ":form", ":c"]);
+    }
+
+    #[test]
+    fn inserting_keeps_preserved_matches_after_their_match() {
+        let mut file = EspansoYaml::parse(MIXED, false).unwrap();
+        file.insert_match(1, pair(":new", "New"));
+        assert_eq!(
+            written(&file),
+            [":a", "(?P<n>\\d+)ft", ":new", ":b", ":form", ":c"]
+        );
+        file.insert_match(0, pair(":first", "First"));
+        assert_eq!(written(&file)[..3], [":first", ":a", "(?P<n>\\d+)ft"]);
+    }
+
+    #[test]
+    fn mixed_files_round_trip() {
+        let file = EspansoYaml::parse(MIXED, false).unwrap();
+        assert_eq!(
+            file.matches,
+            [pair(":a", "A"), pair(":b", "B"), pair(":c", "C")]
+        );
+        let positions: Vec<usize> = file.preserved_matches.iter().map(|(i, _)| *i).collect();
+        assert_eq!(positions, [1, 2]);
+        assert_eq!(file.preserved_notes, ["feet", ""]);
+        assert_eq!(file.lossy_reason(MIXED), None);
+
+        let yaml = file.to_yaml().unwrap();
+        assert_eq!(EspansoYaml::parse(&yaml, false).unwrap(), file);
+        let original: Value = serde_yaml::from_str(MIXED).unwrap();
+        let reread: Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(reread, original);
+    }
+
+    #[test]
+    fn replacements_round_trip_as_blocks_or_escaped() {
+        let mut file = EspansoYaml::default();
+        file.matches.push(YamlPairs {
+            note: "signature".to_string(),
+            ..pair(":sig", "Best,\nAlex \"A\" Smith")
+        });
+        file.matches.push(pair(":one", "one line"));
+
+        let block = file.to_yaml_with(true).unwrap();
+        assert!(block.contains("# note: signature\n- trigger: :sig\n  replace: |"));
+        let escaped = file.to_yaml_with(false).unwrap();
+        assert!(
+            escaped.contains(r#"  replace: "Best,\nAlex \"A\" Smith""#),
+            "{}",
+            escaped
+        );
+        assert!(!escaped.contains('|'));
+        for yaml in [block, escaped] {
+            assert_eq!(EspansoYaml::parse(&yaml, false).unwrap(), file);
+        }
+    }
+
+    #[test]
+    fn lossy_reason_spots_what_saving_drops() {
+        let unknown_key = "matches:\n  - trigger: \":a\"\n    replace: A\nextra: 1\n";
+        let file = EspansoYaml::parse(unknown_key, false).unwrap();
+        assert!(file.lossy_reason(unknown_key).is_some());
+
+        let commented = "# my matches\nmatches:\n  - trigger: \":a\"\n    replace: A\n";
+        let file = EspansoYaml::parse(commented, false).unwrap();
+        assert!(file.lossy_reason(commented).unwrap().contains("comments"));
+
+        // Defaults that aren't written and empty files change nothing
+        let defaults = "matches:\n  - trigger: \":a\"\n    replace: A\n    word: false\n";
+        let file = EspansoYaml::parse(defaults, false).unwrap();
+        assert_eq!(file.lossy_reason(defaults), None);
+        assert_eq!(EspansoYaml::default().lossy_reason(""), None);
+    }
+
+    #[test]
+    fn append_skips_triggers_already_used() {
+        let mut file = EspansoYaml::parse(MIXED, false).unwrap();
+        let other = EspansoYaml::from_snippet(
+            "- trigger: \":a\"\n  replace: again\n- trigger: \":form\"\n  form: \"[[x]]\"\n- trigger: \":d\"\n  replace: D\n",
+        )
+        .unwrap();
+        assert_eq!(file.append(other), (1, 2));
+        assert_eq!(file.matches.last(), Some(&pair(":d", "D")));
+    }
+
+    #[test]
+    fn notes_come_from_the_comments_above_each_match() {
+        let yaml = "matches:
+  # note: first
+  # note: second
+  - trigger: \":a\"
+    replace: A
+    vars:
+      # note: nested, not a match
+      - name: x
+  # an ordinary comment
+  - trigger: \":b\"
+    replace: B
+  # note: last
+  - trigger: \":c\"
+    replace: C
+global_vars: []
+";
+        assert_eq!(match_notes(yaml), ["first second", "", "last"]);
+    }
+
+    #[test]
+    fn non_utf8_files_are_decoded_with_a_warning() {
+        let utf16 = |text: &str, to_bytes: fn(u16) -> [u8; 2], bom: &[u8]| {
+            let mut bytes = bom.to_vec();
+            bytes.extend(text.encode_utf16().flat_map(to_bytes));
+            bytes
+        };
+        for bytes in [
+            utf16("matches: é", u16::to_le_bytes, b"\xFF\xFE"),
+            utf16("matches: é", u16::to_be_bytes, b"\xFE\xFF"),
+        ] {
+            let (text, warning) = decode_yaml_bytes(&bytes);
+            assert_eq!(text, "matches: é");
+            assert!(warning.unwrap().contains("UTF-16"));
+        }
+
+        let (text, warning) = decode_yaml_bytes(b"\xEF\xBB\xBFmatches: []");
+        assert_eq!(text, "matches: []");
+        assert!(warning.unwrap().contains("byte order mark"));
+
+        let (text, warning) = decode_yaml_bytes(b"replace: caf\xE9");
+        assert_eq!(text, "replace: café");
+        assert!(warning.unwrap().contains("Latin-1"));
+
+        assert_eq!(
+            decode_yaml_bytes("café".as_bytes()),
+            ("café".to_string(), None)
+        );
+    }
 }
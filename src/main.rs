@@ -19,16 +19,97 @@ mod app;
 mod egui_data;
 mod espanso_yaml;
 mod parse_config;
+mod snippet_packs;
 mod style;
 
 use app::EGUI;
+use egui_data::EGUIData;
+use std::env;
+use std::path::PathBuf;
+
+const RENDERER_HINT: &str = "espansoGUI couldn't start its graphics renderer. \
+Try launching it with --software-renderer (or ESPANSO_GUI_RENDERER=software) to draw without \
+the GPU, or with --present-mode fifo to use a different present mode.";
 
 pub fn main() -> iced::Result {
-    iced::application(EGUI::title, EGUI::update, EGUI::view)
+    let egui_data = app::read_egui_data().unwrap_or_default();
+    apply_renderer_options(&egui_data, env::args().skip(1));
+    install_renderer_panic_hint();
+    let open_file = file_argument(env::args().skip(1));
+
+    let result = iced::application(EGUI::title, EGUI::update, EGUI::view)
         .subscription(EGUI::subscription)
         .theme(EGUI::theme)
         .font(iced_fonts::REQUIRED_FONT_BYTES)
         .font(iced_fonts::NERD_FONT_BYTES)
         .window_size((1024.0, 768.0))
-        .run()
+        .antialiasing(egui_data.antialiasing)
+        .run_with(move || EGUI::boot(open_file));
+
+    if let Err(iced::Error::GraphicsCreationFailed(err)) = &result {
+        eprintln!("{}\n\n{}", err, RENDERER_HINT);
+    }
+    result
+}
+
+// iced reads its backend and present mode from the environment when the
+// window is created, so the options only need to be set before that.
+// Saved settings come first so command line flags can override them.
+fn apply_renderer_options(egui_data: &EGUIData, mut args: impl Iterator<Item = String>) {
+    if egui_data.software_renderer
+        || env::var("ESPANSO_GUI_RENDERER").is_ok_and(|renderer| renderer == "software")
+    {
+        env::set_var("ICED_BACKEND", "tiny-skia");
+    }
+    if let Some(mode) = &egui_data.present_mode {
+        env::set_var("ICED_PRESENT_MODE", mode);
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--software-renderer" => env::set_var("ICED_BACKEND", "tiny-skia"),
+            "--present-mode" => match args.next() {
+                Some(mode) => env::set_var("ICED_PRESENT_MODE", mode),
+                None => eprintln!("--present-mode needs a value, e.g. fifo or mailbox"),
+            },
+            _ => {}
+        }
+    }
+}
+
+// The first argument that isn't an option, e.g. from "Open with" in a file manager
+fn file_argument(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--present-mode" => {
+                args.next();
+            }
+            option if option.starts_with("--") => {}
+            _ => return Some(PathBuf::from(arg)),
+        }
+    }
+    None
+}
+
+// Some GPU/Wayland setups panic inside wgpu rather than returning an error
+fn install_renderer_panic_hint() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let location = info
+            .location()
+            .map(|location| location.file().to_string())
+            .unwrap_or_default();
+        if ["wgpu", "surface", "present"]
+            .iter()
+            .any(|keyword| payload.to_lowercase().contains(keyword) || location.contains(keyword))
+        {
+            eprintln!("\n{}", RENDERER_HINT);
+        }
+    }));
 }
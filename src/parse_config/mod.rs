@@ -19,14 +19,16 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::espanso_yaml::read_yaml_text;
 use anyhow::Result;
-use serde::Serialize;
-use std::{collections::BTreeMap, path::Path};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use std::{collections::BTreeSet, path::Path};
 use thiserror::Error;
 
 mod yaml_config;
 
-#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub(crate) struct ParsedConfig {
     pub label: Option<String>,
 
@@ -60,7 +62,8 @@ pub(crate) struct ParsedConfig {
     pub paste_shortcut_event_delay: Option<usize>,
     pub inject_delay: Option<usize>,
     pub key_delay: Option<usize>,
-    pub keyboard_layout: Option<BTreeMap<String, String>>,
+    // Kept as read so rules, variant, options etc. survive editing the layout
+    pub keyboard_layout: Option<Mapping>,
     pub evdev_modifier_delay: Option<usize>,
 
     // Includes
@@ -75,18 +78,46 @@ pub(crate) struct ParsedConfig {
     pub filter_class: Option<String>,
     pub filter_exec: Option<String>,
     pub filter_os: Option<String>,
+
+    // List options the file wrote as [a, b] rather than one item per line,
+    // so saving keeps their style
+    #[serde(skip)]
+    pub flow_sequences: BTreeSet<String>,
 }
 
 impl ParsedConfig {
     pub fn load(path: &Path) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
+        Ok(Self::load_with_warning(path)?.0)
+    }
+
+    // Also returns a warning when the file's encoding had to be converted
+    pub fn load_with_warning(path: &Path) -> Result<(Self, Option<String>)> {
+        let (content, warning) = read_yaml_text(path)?;
         match yaml_config::YAMLConfig::parse_from_str(&content) {
-            Ok(config) => Ok(config.try_into()?),
+            Ok(config) => {
+                let mut config: Self = config.try_into()?;
+                config.flow_sequences = flow_sequence_keys(&content);
+                Ok((config, warning))
+            }
             Err(err) => Err(ParsedConfigError::LoadFailed(err).into()),
         }
     }
 }
 
+// Top level keys whose value starts on the same line as a flow sequence
+fn flow_sequence_keys(yaml: &str) -> BTreeSet<String> {
+    yaml.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let is_top_level_key = !key.is_empty()
+                && key
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || character == '_');
+            (is_top_level_key && value.trim_start().starts_with('[')).then(|| key.to_string())
+        })
+        .collect()
+}
+
 #[derive(Error, Debug)]
 pub enum ParsedConfigError {
     #[error("can't load config `{0}`")]
@@ -22,6 +22,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Mapping;
+use std::collections::BTreeSet;
 use std::convert::TryFrom;
 
 use super::ParsedConfig;
@@ -190,18 +191,7 @@ impl TryFrom<YAMLConfig> for ParsedConfig {
             word_separators: yaml_config.word_separators,
             backspace_limit: yaml_config.backspace_limit,
             apply_patch: yaml_config.apply_patch,
-            keyboard_layout: yaml_config.keyboard_layout.map(|mapping| {
-                mapping
-                    .into_iter()
-                    .filter_map(|(key, value)| {
-                        if let (Some(key), Some(value)) = (key.as_str(), value.as_str()) {
-                            Some((key.to_string(), value.to_string()))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
-            }),
+            keyboard_layout: yaml_config.keyboard_layout,
             search_trigger: yaml_config.search_trigger,
             search_shortcut: yaml_config.search_shortcut,
             undo_backspace: yaml_config.undo_backspace,
@@ -233,6 +223,8 @@ impl TryFrom<YAMLConfig> for ParsedConfig {
             filter_exec: yaml_config.filter_exec,
             filter_os: yaml_config.filter_os,
             filter_title: yaml_config.filter_title,
+
+            flow_sequences: BTreeSet::new(),
         })
     }
 }
@@ -0,0 +1,176 @@
+// espansoGUI - GUI to interface with Espanso
+// Copyright (C) 2023 Ricky Kresslein <ricky@unobserved.io>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Starter snippets bundled with the app, appended to a match file from Settings
+
+pub struct SnippetPack {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub yaml: &'static str,
+}
+
+pub const SNIPPET_PACKS: [SnippetPack; 3] = [
+    SnippetPack {
+        name: "Dates and times",
+        description: ":date, :isodate, :time and :now insert the current date or time.",
+        yaml: r#"matches:
+  - trigger: ":date"
+    replace: "{{date}}"
+    vars:
+      - name: date
+        type: date
+        params:
+          format: "%d/%m/%Y"
+  - trigger: ":isodate"
+    replace: "{{date}}"
+    vars:
+      - name: date
+        type: date
+        params:
+          format: "%Y-%m-%d"
+  - trigger: ":time"
+    replace: "{{time}}"
+    vars:
+      - name: time
+        type: date
+        params:
+          format: "%H:%M"
+  - trigger: ":now"
+    replace: "{{now}}"
+    vars:
+      - name: now
+        type: date
+        params:
+          format: "%Y-%m-%d %H:%M"
+"#,
+    },
+    SnippetPack {
+        name: "Symbols",
+        description: "Arrows, math and typographic symbols, e.g. :arrow for → and :deg for °.",
+        yaml: r#"matches:
+  - trigger: ":arrow"
+    replace: "→"
+  - trigger: ":larrow"
+    replace: "←"
+  - trigger: ":check"
+    replace: "✓"
+  - trigger: ":cross"
+    replace: "✗"
+  - trigger: ":deg"
+    replace: "°"
+  - trigger: ":times"
+    replace: "×"
+  - trigger: ":pm"
+    replace: "±"
+  - trigger: ":ne"
+    replace: "≠"
+  - trigger: ":le"
+    replace: "≤"
+  - trigger: ":ge"
+    replace: "≥"
+  - trigger: ":inf"
+    replace: "∞"
+  - trigger: ":mdash"
+    replace: "—"
+  - trigger: ":ellipsis"
+    replace: "…"
+  - trigger: ":copy"
+    replace: "©"
+  - trigger: ":tm"
+    replace: "™"
+  - trigger: ":euro"
+    replace: "€"
+  - trigger: ":shrug"
+    replace: "¯\\_(ツ)_/¯"
+"#,
+    },
+    SnippetPack {
+        name: "Email templates",
+        description: "Common replies such as :thanks, :followup and :regards.",
+        yaml: r#"matches:
+  - trigger: ":thanks"
+    replace: "Thank you for your email. I'll get back to you shortly."
+  - trigger: ":followup"
+    replace: "I wanted to follow up on my previous email. Please let me know if you have any questions."
+  - trigger: ":meet"
+    replace: "Would you be available for a short call this week? Let me know what time works best for you."
+  - trigger: ":ooo"
+    replace: "I'm currently out of the office with limited access to email. I'll reply as soon as I'm back."
+  - trigger: ":regards"
+    replace: "Best regards,\n"
+"#,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::espanso_yaml::EspansoYaml;
+
+    #[test]
+    fn every_pack_reads_as_matches() {
+        let expected: [(&[&str], usize); 3] = [
+            // Date matches need vars, so they're kept as preserved matches
+            (&[":date", ":isodate", ":time", ":now"], 0),
+            (
+                &[
+                    ":arrow",
+                    ":larrow",
+                    ":check",
+                    ":cross",
+                    ":deg",
+                    ":times",
+                    ":pm",
+                    ":ne",
+                    ":le",
+                    ":ge",
+                    ":inf",
+                    ":mdash",
+                    ":ellipsis",
+                    ":copy",
+                    ":tm",
+                    ":euro",
+                    ":shrug",
+                ],
+                17,
+            ),
+            (&[":thanks", ":followup", ":meet", ":ooo", ":regards"], 5),
+        ];
+        for (pack, (triggers, editable)) in SNIPPET_PACKS.iter().zip(expected) {
+            let file = EspansoYaml::from_snippet(pack.yaml)
+                .unwrap_or_else(|err| panic!("{} doesn't parse: {}", pack.name, err));
+            assert_eq!(file.matches.len(), editable, "{}", pack.name);
+            let mut found: Vec<&str> = file
+                .matches
+                .iter()
+                .map(|a_match| a_match.trigger.as_str())
+                .chain(
+                    file.preserved_matches
+                        .iter()
+                        .filter_map(|(_, value)| value.get("trigger")?.as_str()),
+                )
+                .collect();
+            found.sort_unstable();
+            let mut triggers = triggers.to_vec();
+            triggers.sort_unstable();
+            assert_eq!(found, triggers, "{}", pack.name);
+            assert!(file
+                .matches
+                .iter()
+                .all(|a_match| !a_match.replace.is_empty()));
+        }
+    }
+}
@@ -14,14 +14,161 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use iced::widget::container;
-use iced::Theme;
+use iced::theme::palette::{self, Extended, Pair};
+use iced::widget::{button, container, text_input};
+use iced::{Border, Color, Theme};
+
+// Black on white or white on black, with saturated accents and match cards
+// that stand well apart from the window
+pub fn high_contrast(dark: bool, accent: Option<Color>) -> Theme {
+    let (name, palette) = if dark {
+        (
+            "High Contrast Dark",
+            palette::Palette {
+                background: Color::BLACK,
+                text: Color::WHITE,
+                primary: Color::from_rgb8(0x4D, 0xB8, 0xFF),
+                success: Color::from_rgb8(0x3D, 0xDC, 0x84),
+                danger: Color::from_rgb8(0xFF, 0x5C, 0x5C),
+            },
+        )
+    } else {
+        (
+            "High Contrast Light",
+            palette::Palette {
+                background: Color::WHITE,
+                text: Color::BLACK,
+                primary: Color::from_rgb8(0x00, 0x3D, 0xA5),
+                success: Color::from_rgb8(0x00, 0x6B, 0x2E),
+                danger: Color::from_rgb8(0xB0, 0x00, 0x20),
+            },
+        )
+    };
+    let palette = palette::Palette {
+        primary: accent.unwrap_or(palette.primary),
+        ..palette
+    };
+    Theme::custom_with_fn(name.to_string(), palette, move |palette| {
+        let mut extended = Extended::generate(palette);
+        let (weak, strong) = if dark { (0.2, 0.5) } else { (0.8, 0.45) };
+        extended.background.weak = Pair::new(Color::from_rgb(weak, weak, weak), palette.text);
+        extended.background.strong =
+            Pair::new(Color::from_rgb(strong, strong, strong), palette.text);
+        extended
+    })
+}
+
+// The system's light or dark theme with accent in place of its blue
+pub fn with_accent(dark: bool, accent: Color) -> Theme {
+    let (name, palette) = if dark {
+        ("Dark", palette::Palette::DARK)
+    } else {
+        ("Light", palette::Palette::LIGHT)
+    };
+    Theme::custom(
+        format!("{} Accent", name),
+        palette::Palette {
+            primary: accent,
+            ..palette
+        },
+    )
+}
+
+// An accent color as saved in the settings, e.g. "#2E9E6A"
+pub fn parse_accent(hex: &str) -> Option<Color> {
+    let digits = hex.trim().strip_prefix('#').unwrap_or(hex.trim());
+    // Color::parse slices by byte, so anything but hex digits is turned away first
+    if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Color::parse(digits)
+    } else {
+        None
+    }
+}
+
+// A swatch for picking an accent color
+pub fn accent_swatch(
+    color: Color,
+    selected: bool,
+) -> impl Fn(&Theme, button::Status) -> button::Style {
+    move |theme, _status| button::Style {
+        background: Some(color.into()),
+        border: Border {
+            color: if selected {
+                theme.palette().text
+            } else {
+                Color::TRANSPARENT
+            },
+            width: 2.0,
+            radius: 12.0.into(),
+        },
+        ..Default::default()
+    }
+}
+
+fn is_high_contrast(theme: &Theme) -> bool {
+    theme.to_string().starts_with("High Contrast")
+}
 
 pub fn gray_background(theme: &Theme) -> container::Style {
     let palette = theme.extended_palette();
 
     container::Style {
         background: Some(palette.background.weak.color.into()),
+        border: if is_high_contrast(theme) {
+            Border {
+                color: palette.background.base.text,
+                width: 1.0,
+                radius: 0.0.into(),
+            }
+        } else {
+            Border::default()
+        },
+        ..Default::default()
+    }
+}
+
+pub fn invalid_card(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        border: Border {
+            color: palette.danger.base.color,
+            width: 2.0,
+            radius: 0.0.into(),
+        },
+        ..gray_background(theme)
+    }
+}
+
+// For states that stop espanso working, so they stand out from the cards
+pub fn warning_banner(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        background: Some(palette.danger.weak.color.into()),
+        text_color: Some(palette.danger.weak.text),
+        ..gray_background(theme)
+    }
+}
+
+// A field whose text can't be used as typed
+pub fn invalid_input(theme: &Theme, status: text_input::Status) -> text_input::Style {
+    let default = text_input::default(theme, status);
+
+    text_input::Style {
+        border: Border {
+            color: theme.extended_palette().danger.base.color,
+            ..default.border
+        },
+        ..default
+    }
+}
+
+pub fn drop_indicator(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        background: Some(palette.primary.strong.color.into()),
         ..Default::default()
     }
 }